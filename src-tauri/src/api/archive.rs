@@ -0,0 +1,48 @@
+use tauri::{AppHandle, State};
+use crate::core::archive::{ArchiveService, ConflictPolicy, ImportReport};
+use crate::state::AppState;
+use crate::ui::dialogs::{self, Filter};
+
+const ARCHIVE_FILTER: Filter<'static> = ("ServeMe Configuration", &["gz", "tgz"]);
+
+/// Asks for a destination and writes settings, servers, profiles and extensions
+/// there as a `.tar.gz` archive. No secrets are included. Returns the path, or
+/// `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn export_config(app: AppHandle) -> Result<Option<String>, String> {
+    let Some(path) = dialogs::save_file(&app, "serveme-config.tar.gz", ARCHIVE_FILTER).await? else {
+        return Ok(None);
+    };
+    ArchiveService::export(&app, &path)?;
+    Ok(Some(path.display().to_string()))
+}
+
+/// Asks for an archive to import and remembers it for `import_config`, so the
+/// webview never names the file. Returns the file name, or `None` if cancelled.
+#[tauri::command]
+pub async fn pick_config_archive(app: AppHandle, state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let Some(path) = dialogs::open_file(&app, ARCHIVE_FILTER).await? else {
+        return Ok(None);
+    };
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    *state.import_archive.lock().unwrap() = Some(path);
+    Ok(Some(name))
+}
+
+/// Imports the archive chosen with `pick_config_archive`. With `dry_run`
+/// nothing is changed and the report shows what would happen; `on_conflict`
+/// decides whether existing items that differ are kept or replaced.
+#[tauri::command]
+pub async fn import_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    dry_run: bool,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<ImportReport, String> {
+    let path = state.import_archive.lock().unwrap().clone().ok_or("No archive selected")?;
+    let report = ArchiveService::import(&app, &path, dry_run, on_conflict.unwrap_or_default())?;
+    if report.applied {
+        state.import_archive.lock().unwrap().take();
+    }
+    Ok(report)
+}
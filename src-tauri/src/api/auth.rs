@@ -1,9 +1,18 @@
-use base64::{engine::general_purpose, Engine as _};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::Ordering;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter, Manager};
-use crate::core::keychain::KeychainService;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use tauri_plugin_opener::OpenerExt;
+use crate::core::api::service::{SessionInfo, SessionService};
+use crate::core::backend::{self, BackendClient};
+use crate::core::jwt::{TokenVerifier, VerifyError};
+use crate::core::keychain::{KeychainService, LoginMethod, Session};
+use crate::core::oidc::{OidcService, OidcTokens};
+use crate::core::profiles::ProfileService;
+use crate::core::settings::{SettingsService, DEFAULT_REFRESH_INTERVAL_SECS};
+use crate::state::AppState;
+use crate::ui;
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct TokenSet {
@@ -11,27 +20,25 @@ pub struct TokenSet {
     pub refresh: String,
 }
 
-#[derive(Deserialize)]
-struct RefreshResponse {
-    access_token: String,
-    refresh_token: String,
-}
-
-#[derive(Deserialize)]
-struct Config {
-    server_url: String,
-    username: String,
-}
-
-#[derive(Deserialize)]
-struct Claims {
-    exp: i64,
+#[derive(Serialize)]
+pub struct SsoLoginResult {
+    pub access: String,
+    pub refresh: String,
+    pub username: String,
 }
 
 pub fn spawn_background_refresh(handle: AppHandle) {
     tauri::async_runtime::spawn(async move {
         loop {
-            tokio::time::sleep(Duration::from_secs(300)).await;
+            let interval = SettingsService::load(&handle)
+                .map(|s| s.refresh_interval_secs)
+                .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS);
+            let state = handle.state::<AppState>();
+            // A new server or profile is checked right away instead of at the next tick.
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = state.refresh_wakeup.notified() => {}
+            }
             let _ = refresh_if_needed(&handle).await.map_err(|e| {
                 eprintln!("[Background Refresh] Failed: {}", e);
             });
@@ -40,82 +47,229 @@ pub fn spawn_background_refresh(handle: AppHandle) {
 }
 
 async fn refresh_if_needed(handle: &AppHandle) -> Result<(), String> {
-    let session = match KeychainService::get_session() {
+    let profile = ProfileService::active_name(handle);
+    let session = match KeychainService::get_session(&profile) {
         Ok(s) => s,
         Err(_) => return Ok(()),
     };
 
-    if !should_refresh(&session.access_token)? {
+    if !should_refresh(handle, &session.access_token).await {
         return Ok(());
     }
 
-    let config = load_config(handle)?;
-    let response = request_new_tokens(&config, &session.refresh_token).await?;
-    
-    KeychainService::save_session(&response.access_token, &response.refresh_token)?;
+    refresh_session_tokens(handle, &profile, session).await?;
+    Ok(())
+}
+
+/// Refreshes through the backend or the identity provider, depending on how
+/// the session was created, and stores the result for the given profile.
+async fn refresh_session_tokens(handle: &AppHandle, profile: &str, session: Session) -> Result<TokenSet, String> {
+    let config = SettingsService::load(handle)?;
 
-    let new_tokens = TokenSet {
-        access: response.access_token,
-        refresh: response.refresh_token,
+    let (access, refresh) = match session.method {
+        LoginMethod::Password => {
+            // ERR_AUTH on a rejected refresh token tells the frontend to log out instead of retrying.
+            let response = BackendClient::with_base_url(handle, &config.server_url)?
+                .refresh(config.username.as_deref(), &session.refresh_token)
+                .await?;
+            (response.access_token, response.refresh_token)
+        }
+        LoginMethod::Sso => {
+            let oidc = config.oidc().ok_or("Single sign-on is not configured")?;
+            let http = backend::http_client(handle)?;
+            let response = OidcService::refresh(&http, &oidc, &session.refresh_token).await?;
+            // Providers without refresh token rotation keep the old one valid.
+            (response.access_token, response.refresh_token.unwrap_or(session.refresh_token))
+        }
     };
-    let _ = handle.emit("tokens-refreshed", new_tokens);
 
+    TokenVerifier::verify(handle, &access).await?;
+    KeychainService::save_session(profile, &Session {
+        access_token: access.clone(),
+        refresh_token: refresh.clone(),
+        method: session.method,
+    })?;
+
+    let new_tokens = TokenSet { access, refresh };
+    let _ = handle.emit("tokens-refreshed", new_tokens.clone());
+
+    Ok(new_tokens)
+}
+
+/// A token that no longer verifies (e.g. the keys were rotated) is refreshed
+/// as well; the new one is verified before it is stored. When the keys cannot
+/// be loaded at all the tick is skipped: the new token would fail the same
+/// way and never be stored, while the old refresh token would already be spent.
+async fn should_refresh(handle: &AppHandle, access_token: &str) -> bool {
+    match TokenVerifier::verify(handle, access_token).await {
+        Ok(claims) => claims.exp - Utc::now().timestamp() <= 600,
+        Err(VerifyError::Invalid(e)) => {
+            eprintln!("[Background Refresh] Stored token did not verify: {}", e);
+            true
+        }
+        Err(VerifyError::Unavailable(e)) => {
+            eprintln!("[Background Refresh] Skipping, cannot verify tokens right now: {}", e);
+            false
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn login_password(app: AppHandle, username: String, password: String) -> Result<TokenSet, String> {
+    let tokens = BackendClient::new(&app)?.login(&username, &password).await?;
+    TokenVerifier::verify(&app, &tokens.access_token).await?;
+
+    KeychainService::save_session(&ProfileService::active_name(&app), &Session {
+        access_token: tokens.access_token.clone(),
+        refresh_token: tokens.refresh_token.clone(),
+        method: LoginMethod::Password,
+    })?;
+    app.state::<AppState>().servers.wake();
+    let _ = app.emit("session-changed", true);
+
+    Ok(TokenSet {
+        access: tokens.access_token,
+        refresh: tokens.refresh_token,
+    })
+}
+
+/// Revokes the refresh token on the backend (best effort) and drops the local session.
+#[tauri::command]
+pub async fn logout<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let profile = ProfileService::active_name(&app);
+
+    if let Ok(session) = KeychainService::get_session(&profile) {
+        if session.method == LoginMethod::Password {
+            let result = match BackendClient::new(&app) {
+                Ok(client) => client.logout(&session.refresh_token).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                eprintln!("[Auth] Backend logout failed: {}", e);
+            }
+        }
+    }
+
+    KeychainService::purge_session(&profile)?;
+    app.state::<AppState>().servers.wake();
+    let _ = app.emit("session-changed", false);
     Ok(())
 }
 
-fn should_refresh(access_token: &str) -> Result<bool, String> {
-    let parts: Vec<&str> = access_token.split('.').collect();
-    if parts.len() != 3 {
-        return Err("Invalid JWT".into());
+#[tauri::command]
+pub async fn login_sso(app: AppHandle) -> Result<SsoLoginResult, String> {
+    let config = SettingsService::load(&app)?;
+    let oidc = config.oidc().ok_or("Single sign-on is not configured for this profile")?;
+    let http = backend::http_client(&app)?;
+
+    let discovery = OidcService::discover(&http, &oidc).await?;
+    let request = OidcService::begin_pkce(&oidc, &discovery).await?;
+
+    app.opener()
+        .open_url(request.authorize_url.clone(), None::<&str>)
+        .map_err(|e| format!("Failed to open browser: {}", e))?;
+
+    let tokens = OidcService::complete_pkce(&http, &oidc, &discovery, request).await?;
+    store_idp_login(&app, tokens).await
+}
+
+/// Device authorization grant for machines where a loopback redirect is not an option.
+/// The user code is shown in the dashboard (via events) and in the tray until the login ends.
+#[tauri::command]
+pub async fn login_device(app: AppHandle, state: State<'_, AppState>) -> Result<SsoLoginResult, String> {
+    // Claimed before the first await, so two calls cannot both get past here.
+    if state.device_login_active.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_err() {
+        return Err("A device login is already in progress".into());
+    }
+    let _slot = DeviceLoginSlot(&state);
+    state.device_login_cancelled.store(false, Ordering::Relaxed);
+
+    let config = SettingsService::load(&app)?;
+    let oidc = config.oidc().ok_or("Single sign-on is not configured for this profile")?;
+    let http = backend::http_client(&app)?;
+
+    let discovery = OidcService::discover(&http, &oidc).await?;
+    let authorization = OidcService::begin_device(&http, &oidc, &discovery).await?;
+
+    *state.device_login.lock().unwrap() = Some(authorization.clone());
+    let _ = app.emit("device-login-pending", &authorization);
+    let _ = ui::tray::refresh(&app);
+
+    let result = OidcService::poll_device(&http, &oidc, &discovery, &authorization, &state.device_login_cancelled).await;
+
+    *state.device_login.lock().unwrap() = None;
+    let _ = app.emit("device-login-finished", result.is_ok());
+    let _ = ui::tray::refresh(&app);
+
+    store_idp_login(&app, result?).await
+}
+
+/// Frees the device login slot however `login_device` returns.
+struct DeviceLoginSlot<'a>(&'a AppState);
+
+impl Drop for DeviceLoginSlot<'_> {
+    fn drop(&mut self) {
+        *self.0.device_login.lock().unwrap() = None;
+        self.0.device_login_active.store(false, Ordering::Release);
     }
+}
+
+#[tauri::command]
+pub async fn cancel_device_login(state: State<'_, AppState>) -> Result<(), String> {
+    state.device_login_cancelled.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+async fn store_idp_login(app: &AppHandle, tokens: OidcTokens) -> Result<SsoLoginResult, String> {
+    let refresh = tokens.refresh_token
+        .ok_or("Identity provider did not return a refresh token")?;
+    // Only from the verified access token; the ID token is not checked here.
+    let username = TokenVerifier::verify(app, &tokens.access_token).await?
+        .display_name()
+        .ok_or("Could not determine the username from the login response")?;
+
+    KeychainService::save_session(&ProfileService::active_name(app), &Session {
+        access_token: tokens.access_token.clone(),
+        refresh_token: refresh.clone(),
+        method: LoginMethod::Sso,
+    })?;
+    app.state::<AppState>().servers.wake();
+    let _ = app.emit("session-changed", true);
+
+    Ok(SsoLoginResult {
+        access: tokens.access_token,
+        refresh,
+        username,
+    })
+}
+
+#[tauri::command]
+pub async fn refresh_session(app: AppHandle) -> Result<TokenSet, String> {
+    let profile = ProfileService::active_name(&app);
+    let session = KeychainService::get_session(&profile)?;
+    refresh_session_tokens(&app, &profile, session).await
+}
 
-    let payload = general_purpose::URL_SAFE_NO_PAD
-        .decode(parts[1])
-        .map_err(|e| e.to_string())?;
-
-    let claims: Claims = serde_json::from_slice(&payload).map_err(|e| e.to_string())?;
-    let now = Utc::now().timestamp();
-    
-    Ok(claims.exp - now <= 600)
-}
-
-fn load_config(handle: &AppHandle) -> Result<Config, String> {
-    let path = handle.path().app_config_dir()
-        .map_err(|e| e.to_string())?
-        .join("settings.json");
-    
-    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&content).map_err(|e| e.to_string())
-}
-
-async fn request_new_tokens(config: &Config, refresh_token: &str) -> Result<RefreshResponse, String> {
-    let url = format!("{}/api/v1/token/refresh", config.server_url.trim_end_matches('/'));
-    
-    reqwest::Client::builder()
-        .user_agent("ServeMe/0.2.0")
-        .build()
-        .map_err(|e| e.to_string())?
-        .post(url)
-        .json(&serde_json::json!({
-            "username": config.username,
-            "refresh_token": refresh_token
-        }))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .json::<RefreshResponse>()
-        .await
-        .map_err(|e| e.to_string())
+#[tauri::command]
+pub async fn get_session_info(app: AppHandle) -> Result<SessionInfo, String> {
+    SessionService::get_session_info(&app).await
 }
 
 #[tauri::command]
-pub async fn save_tokens(access_token: &str, refresh_token: &str) -> Result<(), String> {
-    KeychainService::save_session(access_token, refresh_token)
+pub async fn save_tokens(app: AppHandle, access_token: &str, refresh_token: &str) -> Result<(), String> {
+    TokenVerifier::verify(&app, access_token).await?;
+
+    let profile = ProfileService::active_name(&app);
+    KeychainService::save_session(&profile, &Session {
+        access_token: access_token.to_string(),
+        refresh_token: refresh_token.to_string(),
+        method: LoginMethod::Password,
+    })
 }
 
 #[tauri::command]
-pub async fn get_tokens() -> Result<TokenSet, String> {
-    let session = KeychainService::get_session()?;
+pub async fn get_tokens(app: AppHandle) -> Result<TokenSet, String> {
+    let session = TokenVerifier::verified_session(&app, &ProfileService::active_name(&app)).await?;
     Ok(TokenSet {
         access: session.access_token,
         refresh: session.refresh_token,
@@ -123,18 +277,18 @@ pub async fn get_tokens() -> Result<TokenSet, String> {
 }
 
 #[tauri::command]
-pub async fn get_refresh_token() -> Result<String, String> {
-    let session = KeychainService::get_session()?;
+pub async fn get_refresh_token(app: AppHandle) -> Result<String, String> {
+    let session = TokenVerifier::verified_session(&app, &ProfileService::active_name(&app)).await?;
     Ok(session.refresh_token)
 }
 
 #[tauri::command]
-pub async fn get_access_token() -> Result<String, String> {
-    let session = KeychainService::get_session()?;
+pub async fn get_access_token(app: AppHandle) -> Result<String, String> {
+    let session = TokenVerifier::verified_session(&app, &ProfileService::active_name(&app)).await?;
     Ok(session.access_token)
 }
 
 #[tauri::command]
-pub async fn purge_tokens() -> Result<(), String> {
-    KeychainService::purge_session()
+pub async fn purge_tokens(app: AppHandle) -> Result<(), String> {
+    KeychainService::purge_session(&ProfileService::active_name(&app))
 }
@@ -0,0 +1,30 @@
+use tauri::AppHandle;
+use crate::core::backend::{validate_server_id, AccessStatus, BackendClient};
+
+/// Health check against a candidate URL before it is saved.
+#[tauri::command]
+pub async fn check_connection(app: AppHandle, url: String) -> Result<(), String> {
+    BackendClient::with_base_url(&app, &url)?.health().await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_access_status(app: AppHandle, server: String) -> Result<AccessStatus, String> {
+    validate_server_id(&server)?;
+    let (client, token) = BackendClient::authorized(&app).await?;
+    Ok(client.status(&token, &server).await?)
+}
+
+#[tauri::command]
+pub async fn server_exists(app: AppHandle, server: String) -> Result<bool, String> {
+    validate_server_id(&server)?;
+    let (client, token) = BackendClient::authorized(&app).await?;
+    Ok(client.exists(&token, &server).await?)
+}
+
+#[tauri::command]
+pub async fn request_access(app: AppHandle, server: String) -> Result<(), String> {
+    validate_server_id(&server)?;
+    let (client, token) = BackendClient::authorized(&app).await?;
+    Ok(client.access(&token, &server).await?)
+}
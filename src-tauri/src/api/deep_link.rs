@@ -0,0 +1,34 @@
+use tauri::{AppHandle, State};
+use crate::api::extensions::run_extension;
+use crate::core::deep_link::{DeepLinkAction, PendingDeepLink, LINKABLE_SETTINGS};
+use crate::core::servers::ServerService;
+use crate::core::settings::SettingsService;
+use crate::state::AppState;
+use crate::ui::events::instance::request_access_and_notify;
+
+/// Links that arrived before the dashboard was listening, e.g. the one that launched the app.
+#[tauri::command]
+pub async fn list_deep_links(state: State<'_, AppState>) -> Result<Vec<PendingDeepLink>, String> {
+    Ok(state.deep_links.list())
+}
+
+/// Runs a queued link action after the user confirmed it. The action is
+/// validated again, since settings may have changed while the prompt was open.
+#[tauri::command]
+pub async fn confirm_deep_link(app: AppHandle, state: State<'_, AppState>, id: u64) -> Result<(), String> {
+    let action = state.deep_links.take(id).ok_or("This link has already been handled")?;
+    action.validate(&app)?;
+
+    match action {
+        DeepLinkAction::Access { server } => request_access_and_notify(app, server).await,
+        DeepLinkAction::AddServer { server } => ServerService::add(&app, &server).await.map(|_| ()),
+        DeepLinkAction::RunExtension { id } => run_extension(app, state, id).await,
+        DeepLinkAction::UpdateSettings { patch } => SettingsService::update(&app, patch, &LINKABLE_SETTINGS).map(|_| ()),
+    }
+}
+
+#[tauri::command]
+pub async fn dismiss_deep_link(state: State<'_, AppState>, id: u64) -> Result<(), String> {
+    state.deep_links.take(id);
+    Ok(())
+}
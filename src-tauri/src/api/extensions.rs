@@ -1,10 +1,15 @@
+use crate::constants::LOCAL_API_URL;
+use crate::core::profiles::{Profile, ProfileService};
+use crate::core::settings::SettingsService;
 use crate::state::AppState;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
 const VERSION_SEPARATOR: &str = " - ";
+const EXIT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -22,42 +27,14 @@ pub async fn list_extensions<R: Runtime>(
     app: AppHandle<R>,
     state: State<'_, AppState>,
 ) -> Result<Vec<ExtensionInfo>, String> {
-    let extensions_dir = get_extensions_dir(&app)?;
-    if !extensions_dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut list = Vec::new();
-    let mut running = state.running_extensions.lock().unwrap();
-
-    for entry in fs::read_dir(extensions_dir).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-
-        if !is_valid_extension_item(&path) {
-            continue;
-        }
-
-        if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
-            let id = filename.to_string();
-            let is_running = check_and_update_running_status(&app, &mut running, &id);
-
-            list.push(ExtensionInfo {
-                id,
-                name: get_base_name(filename),
-                version: get_version(filename),
-                is_running,
-            });
-        }
-    }
-
-    list.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    Ok(list)
+    ensure_enabled(&app)?;
+    installed_extensions(&app, &state)
 }
 
 #[tauri::command]
 pub async fn upload_extension<R: Runtime>(
     app: AppHandle<R>,
+    state: State<'_, AppState>,
     source_path: String,
 ) -> Result<(), String> {
     let source = PathBuf::from(&source_path);
@@ -69,6 +46,8 @@ pub async fn upload_extension<R: Runtime>(
         .and_then(|n| n.to_str())
         .ok_or_else(|| "Invalid source filename".to_string())?
         .to_string();
+    state.policy.check_extension(&get_base_name(&name))?;
+    ensure_enabled(&app)?;
 
     let extensions_dir = get_extensions_dir(&app)?;
     fs::create_dir_all(&extensions_dir)
@@ -91,6 +70,7 @@ pub async fn upload_extension<R: Runtime>(
         set_permissions_unix(&target_path)?;
     }
 
+    let _ = app.emit("extensions-changed", ());
     Ok(())
 }
 
@@ -100,6 +80,9 @@ pub async fn run_extension<R: Runtime>(
     state: State<'_, AppState>,
     id: String,
 ) -> Result<(), String> {
+    validate_extension_id(&id)?;
+    state.policy.check_extension(&get_base_name(&id))?;
+    ensure_enabled(&app)?;
     let mut running = state.running_extensions.lock().unwrap();
 
     if is_already_running(&mut running, &id) {
@@ -111,8 +94,13 @@ pub async fn run_extension<R: Runtime>(
         return Err(format!("Extension '{}' not found", id));
     }
 
-    let child = spawn_extension_process(&path, &id)?;
+    let profile = ProfileService::active(&app)?;
+    let child = spawn_extension_process(&path, &id, &profile)?;
+    state.crashed_extensions.lock().unwrap().remove(&id);
     running.insert(id, child);
+    drop(running);
+
+    let _ = app.emit("extensions-changed", ());
     Ok(())
 }
 
@@ -122,10 +110,12 @@ pub async fn stop_extension<R: Runtime>(
     state: State<'_, AppState>,
     id: String,
 ) -> Result<(), String> {
-    let mut running = state.running_extensions.lock().unwrap();
-    if let Some(child) = running.remove(&id) {
+    if let Some(child) = state.running_extensions.lock().unwrap().remove(&id) {
         terminate_extension(&app, &id, child);
     }
+    state.extension_subscriptions.lock().unwrap().remove(&id);
+
+    let _ = app.emit("extensions-changed", ());
     Ok(())
 }
 
@@ -135,12 +125,15 @@ pub async fn delete_extension<R: Runtime>(
     state: State<'_, AppState>,
     id: String,
 ) -> Result<(), String> {
+    validate_extension_id(&id)?;
+    ensure_enabled(&app)?;
     {
         let mut running = state.running_extensions.lock().unwrap();
         if let Some(child) = running.remove(&id) {
             terminate_extension(&app, &id, child);
         }
     }
+    state.crashed_extensions.lock().unwrap().remove(&id);
 
     let path = get_extension_path(&app, &id)?;
     if path.exists() {
@@ -151,24 +144,125 @@ pub async fn delete_extension<R: Runtime>(
         }
     }
 
+    let _ = app.emit("extensions-changed", ());
     Ok(())
 }
 
-pub fn cleanup_processes<R: Runtime>(app: &AppHandle<R>, state: &AppState) {
+/// Every extension in the extensions directory, sorted by name. Does not check
+/// whether extensions are enabled; callers decide that.
+pub fn installed_extensions<R: Runtime>(app: &AppHandle<R>, state: &AppState) -> Result<Vec<ExtensionInfo>, String> {
+    let extensions_dir = get_extensions_dir(app)?;
+    if !extensions_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut list = Vec::new();
+    let mut running = state.running_extensions.lock().unwrap();
+
+    for entry in fs::read_dir(extensions_dir).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path();
+
+        if !is_valid_extension_item(&path) {
+            continue;
+        }
+
+        if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
+            let id = filename.to_string();
+            let is_running = check_and_update_running_status(app, &mut running, &id);
+
+            list.push(ExtensionInfo {
+                id,
+                name: get_base_name(filename),
+                version: get_version(filename),
+                is_running,
+            });
+        }
+    }
+
+    list.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(list)
+}
+
+/// Stops and starts a running extension so it picks up new settings. Extensions
+/// that are not running are left alone; one that may no longer run (disabled,
+/// or refused by policy) stays stopped.
+pub fn restart_extension<R: Runtime>(app: &AppHandle<R>, state: &AppState, id: &str) -> Result<(), String> {
     let mut running = state.running_extensions.lock().unwrap();
-    for (id, child) in running.drain() {
+    let Some(child) = running.remove(id) else {
+        return Ok(());
+    };
+    terminate_extension(app, id, child);
+    // A fresh process declares its interests again.
+    state.extension_subscriptions.lock().unwrap().remove(id);
+
+    let allowed = state.policy.check_extension(&get_base_name(id)).and_then(|_| ensure_enabled(app));
+    if let Err(e) = allowed {
+        drop(running);
+        let _ = app.emit("extensions-changed", ());
+        return Err(e);
+    }
+
+    let path = get_extension_path(app, id)?;
+    let profile = ProfileService::active(app)?;
+    let child = spawn_extension_process(&path, id, &profile)?;
+    running.insert(id.to_string(), child);
+    Ok(())
+}
+
+pub fn cleanup_processes<R: Runtime>(app: &AppHandle<R>, state: &AppState) {
+    state.crashed_extensions.lock().unwrap().clear();
+    let stopped: Vec<(String, Child)> = state.running_extensions.lock().unwrap().drain().collect();
+    if stopped.is_empty() {
+        return;
+    }
+    for (id, child) in stopped {
         terminate_extension(app, &id, child);
     }
+    state.extension_subscriptions.lock().unwrap().clear();
+    let _ = app.emit("extensions-changed", ());
+}
+
+/// Notices extensions that exited on their own, so a crash shows up (tray icon,
+/// `extension-crash`) without the extensions page being open.
+pub fn spawn_exit_monitor<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(EXIT_CHECK_INTERVAL).await;
+
+            let state = app.state::<AppState>();
+            let exited = {
+                let mut running = state.running_extensions.lock().unwrap();
+                let ids: Vec<String> = running.keys().cloned().collect();
+                ids.iter().filter(|id| !check_and_update_running_status(&app, &mut running, id)).count() > 0
+            };
+            if exited {
+                let _ = app.emit("extensions-changed", ());
+            }
+        }
+    });
+}
+
+// --- Helpers: Settings ---
+
+// The toggle lives in settings.json, so it is checked here rather than trusted to the UI.
+fn ensure_enabled<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    if SettingsService::load(app)?.extensions_enabled {
+        Ok(())
+    } else {
+        Err("Extensions are disabled in settings".into())
+    }
 }
 
 // --- Helpers: Process Management ---
 
-fn spawn_extension_process(path: &Path, id: &str) -> Result<Child, String> {
+fn spawn_extension_process(path: &Path, id: &str, profile: &Profile) -> Result<Child, String> {
     #[cfg(target_os = "macos")]
     {
         if is_macos_app(path) {
             let mut cmd = Command::new("open");
             cmd.arg("-W").arg(path);
+            apply_extension_env(&mut cmd, id, profile);
             return cmd.spawn().map_err(|e| format!("Failed to spawn extension '{}': {}", id, e));
         }
     }
@@ -178,15 +272,25 @@ fn spawn_extension_process(path: &Path, id: &str) -> Result<Child, String> {
         if is_windows_script(path) {
             let mut cmd = Command::new("cmd");
             cmd.arg("/C").arg(path);
+            apply_extension_env(&mut cmd, id, profile);
             return cmd.spawn().map_err(|e| format!("Failed to spawn extension '{}': {}", id, e));
         }
     }
 
-    Command::new(path)
-        .spawn()
+    let mut cmd = Command::new(path);
+    apply_extension_env(&mut cmd, id, profile);
+    cmd.spawn()
         .map_err(|e| format!("Failed to spawn extension '{}': {}", id, e))
 }
 
+// Extensions learn which profile they run under without having to query the local API first.
+fn apply_extension_env(cmd: &mut Command, id: &str, profile: &Profile) {
+    cmd.env("SERVEME_EXTENSION_ID", id)
+        .env("SERVEME_PROFILE", &profile.name)
+        .env("SERVEME_SERVER_URL", &profile.server_url)
+        .env("SERVEME_LOCAL_API", LOCAL_API_URL);
+}
+
 #[cfg(target_os = "windows")]
 fn is_windows_script(path: &Path) -> bool {
     path.extension()
@@ -220,6 +324,7 @@ fn check_and_update_running_status<R: Runtime>(
             Ok(None) => return true,
             Ok(Some(status)) => {
                 if !status.success() {
+                    app.state::<AppState>().crashed_extensions.lock().unwrap().insert(id.to_string());
                     let name = get_base_name(id);
                     let _ = app.emit("extension-crash", format!("Extension '{}' exited with an error.", name));
                 }
@@ -245,7 +350,7 @@ fn is_already_running(
 
 // --- Helpers: Filesystem ---
 
-fn get_extensions_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+pub fn get_extensions_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
     app.path()
         .app_data_dir()
         .map(|mut p| {
@@ -256,10 +361,20 @@ fn get_extensions_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String>
 }
 
 fn get_extension_path<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<PathBuf, String> {
+    validate_extension_id(id)?;
     Ok(get_extensions_dir(app)?.join(id))
 }
 
-fn is_valid_extension_item(path: &Path) -> bool {
+/// An id is a file name directly inside the extensions directory; anything that
+/// could point elsewhere (`../`, absolute or drive paths, hidden files) is refused.
+pub fn validate_extension_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.contains(['/', '\\', ':', '\0']) || id.starts_with('.') {
+        return Err(format!("'{}' is not a valid extension id", id));
+    }
+    Ok(())
+}
+
+pub fn is_valid_extension_item(path: &Path) -> bool {
     path.is_file() || is_macos_app(path)
 }
 
@@ -298,7 +413,7 @@ fn set_permissions_unix(path: &Path) -> Result<(), String> {
 
 // --- Helpers: Naming & Versioning ---
 
-fn handle_single_version_constraint(name: &str, extensions_dir: &Path) -> Result<(), String> {
+pub fn handle_single_version_constraint(name: &str, extensions_dir: &Path) -> Result<(), String> {
     let base_name = get_base_name(name);
     if extensions_dir.exists() {
         for entry in fs::read_dir(extensions_dir).map_err(|e| e.to_string())? {
@@ -324,7 +439,7 @@ fn get_version(filename: &str) -> String {
     }
 }
 
-fn get_base_name(filename: &str) -> String {
+pub fn get_base_name(filename: &str) -> String {
     let stem = Path::new(filename).file_stem().and_then(|s| s.to_str()).unwrap_or(filename);
     if let Some(index) = stem.rfind(VERSION_SEPARATOR) {
         stem[..index].trim().to_string()
@@ -332,3 +447,24 @@ fn get_base_name(filename: &str) -> String {
         stem.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_ids_stay_inside_the_extensions_dir() {
+        assert!(validate_extension_id("deploy-tool - 1.2.0.bin").is_ok());
+        assert!(validate_extension_id("Notes.app").is_ok());
+
+        for id in ["", "../../usr/bin/deploy-tool", "..", ".hidden", "sub/tool", "sub\\tool", "/usr/bin/tool", "C:tool"] {
+            assert!(validate_extension_id(id).is_err(), "{:?} should be rejected", id);
+        }
+    }
+
+    #[test]
+    fn base_name_drops_version_and_extension() {
+        assert_eq!(get_base_name("deploy-tool - 1.2.0.bin"), "deploy-tool");
+        assert_eq!(get_base_name("Notes.app"), "Notes");
+    }
+}
@@ -0,0 +1,31 @@
+use tauri::AppHandle;
+use crate::core::history::{AccessHistoryService, ExportFormat, HistoryQuery, HistoryRecord};
+use crate::ui::dialogs;
+
+/// Access history matching `query`, newest first.
+#[tauri::command]
+pub async fn query_access_history(app: AppHandle, query: Option<HistoryQuery>, limit: Option<usize>) -> Result<Vec<HistoryRecord>, String> {
+    let mut records = AccessHistoryService::query(&app, &query.unwrap_or_default())?;
+    records.reverse();
+    if let Some(limit) = limit {
+        records.truncate(limit);
+    }
+    Ok(records)
+}
+
+/// Asks for a destination and writes the history matching `query` there, oldest
+/// first. The path comes from the dialog, never from the webview. Returns the
+/// number of records, or `None` if the dialog was cancelled.
+#[tauri::command]
+pub async fn export_access_history(app: AppHandle, format: ExportFormat, query: Option<HistoryQuery>) -> Result<Option<usize>, String> {
+    let extension = match format {
+        ExportFormat::Csv => "csv",
+        ExportFormat::Json => "json",
+    };
+
+    let file_name = format!("serveme-access-history.{}", extension);
+    let Some(path) = dialogs::save_file(&app, &file_name, (&extension.to_uppercase(), &[extension])).await? else {
+        return Ok(None);
+    };
+    AccessHistoryService::export(&app, &query.unwrap_or_default(), format, &path).map(Some)
+}
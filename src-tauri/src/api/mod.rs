@@ -1,3 +1,11 @@
 pub mod system;
 pub mod extensions;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod profiles;
+pub mod backend;
+pub mod network;
+pub mod settings;pub mod archive;
+pub mod deep_link;
+pub mod servers;
+pub mod schedules;
+pub mod history;
@@ -0,0 +1,110 @@
+use tauri::AppHandle;
+use crate::core::backend::{self, BackendClient, BackendError};
+use crate::core::keychain::{KeychainService, ProxyCredentials};
+use crate::core::proxy::ProxySettings;
+use crate::core::tls::{self, TlsSettings};
+
+#[tauri::command]
+pub async fn get_tls_settings(app: AppHandle) -> Result<TlsSettings, String> {
+    TlsSettings::load(&app)
+}
+
+/// Saves the TLS settings after checking that a client can be built from them.
+/// `client_key_path` imports a PEM key into the keychain; the file itself is not kept.
+#[tauri::command]
+pub async fn update_tls_settings(
+    app: AppHandle,
+    settings: TlsSettings,
+    client_key_path: Option<String>,
+) -> Result<TlsSettings, String> {
+    let previous_key = KeychainService::get_client_key()?;
+
+    match (&settings.client_cert, client_key_path) {
+        (Some(_), Some(path)) => KeychainService::save_client_key(&tls::read_client_key(&path)?)?,
+        (None, _) => KeychainService::purge_client_key()?,
+        (Some(_), None) => {}
+    }
+
+    // The key is already in the keychain, so a failure from here on puts the old one back.
+    if let Err(e) = save_tls_settings(&app, settings) {
+        restore_client_key(previous_key)?;
+        backend::reset_http_client(&app);
+        return Err(e);
+    }
+
+    backend::reset_http_client(&app);
+    TlsSettings::load(&app)
+}
+
+#[tauri::command]
+pub async fn get_proxy_settings(app: AppHandle) -> Result<ProxySettings, String> {
+    ProxySettings::load(&app)
+}
+
+/// Saves the proxy settings. A missing `password` keeps the stored one, so the
+/// UI never has to read it back; clearing the username removes both.
+#[tauri::command]
+pub async fn update_proxy_settings(
+    app: AppHandle,
+    settings: ProxySettings,
+    password: Option<String>,
+) -> Result<ProxySettings, String> {
+    settings.validate()?;
+    let credentials = resolve_credentials(&settings, password)?;
+    backend::build_client(&TlsSettings::load(&app)?, &settings, credentials.as_ref())?;
+
+    match &credentials {
+        Some(credentials) => KeychainService::save_proxy_credentials(credentials)?,
+        None => KeychainService::purge_proxy_credentials()?,
+    }
+    settings.save(&app)?;
+    backend::reset_http_client(&app);
+    ProxySettings::load(&app)
+}
+
+/// Calls the backend health endpoint through the given (unsaved) proxy settings.
+/// Fails with the usual `ERR_*` codes, or with the reason the client could not be built.
+#[tauri::command]
+pub async fn test_proxy_settings(
+    app: AppHandle,
+    settings: ProxySettings,
+    password: Option<String>,
+) -> Result<(), String> {
+    settings.validate()?;
+    let credentials = resolve_credentials(&settings, password)?;
+    let http = backend::build_client(&TlsSettings::load(&app)?, &settings, credentials.as_ref())?;
+    let server_url = backend::active_server_url(&app).ok_or(BackendError::Offline)?;
+
+    BackendClient::with_client(http, &server_url).health().await?;
+    Ok(())
+}
+
+fn resolve_credentials(settings: &ProxySettings, password: Option<String>) -> Result<Option<ProxyCredentials>, String> {
+    let Some(username) = settings.username.clone().filter(|u| !u.is_empty()) else {
+        return Ok(None);
+    };
+
+    let password = match password {
+        Some(password) => password,
+        None => KeychainService::get_proxy_credentials()?
+            .map(|c| c.password)
+            .unwrap_or_default(),
+    };
+    Ok(Some(ProxyCredentials { username, password }))
+}
+
+/// Checks that a client can be built with the (unsaved) settings and the key now in the keychain, then saves them.
+fn save_tls_settings(app: &AppHandle, settings: TlsSettings) -> Result<(), String> {
+    let settings = settings.with_pinned_host(TlsSettings::load(app)?.pinned_host);
+    let proxy = ProxySettings::load(app)?;
+    let credentials = KeychainService::get_proxy_credentials()?;
+    backend::build_client(&settings, &proxy, credentials.as_ref())?;
+    settings.save(app)
+}
+
+fn restore_client_key(key: Option<String>) -> Result<(), String> {
+    match key {
+        Some(pem) => KeychainService::save_client_key(&pem),
+        None => KeychainService::purge_client_key(),
+    }
+}
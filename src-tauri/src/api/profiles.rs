@@ -0,0 +1,29 @@
+use tauri::{AppHandle, Emitter};
+use crate::core::profiles::{Profile, ProfileService};
+
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> Result<Vec<Profile>, String> {
+    ProfileService::list(&app)
+}
+
+#[tauri::command]
+pub async fn get_active_profile(app: AppHandle) -> Result<Profile, String> {
+    ProfileService::active(&app)
+}
+
+#[tauri::command]
+pub async fn create_profile(app: AppHandle, profile: Profile) -> Result<(), String> {
+    ProfileService::create(&app, profile)
+}
+
+#[tauri::command]
+pub async fn switch_profile(app: AppHandle, name: String) -> Result<Profile, String> {
+    let profile = ProfileService::switch(&app, &name)?;
+    let _ = app.emit("profile-changed", profile.clone());
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, name: String) -> Result<(), String> {
+    ProfileService::delete(&app, &name)
+}
@@ -0,0 +1,38 @@
+use chrono::{DateTime, Local};
+use tauri::AppHandle;
+use crate::core::scheduler::{JobRecord, ScheduleEntry, ScheduleRule, ScheduleTarget, SchedulerService};
+
+#[tauri::command]
+pub async fn list_schedules(app: AppHandle) -> Result<Vec<ScheduleEntry>, String> {
+    SchedulerService::list(&app)
+}
+
+#[tauri::command]
+pub async fn add_schedule(app: AppHandle, target: ScheduleTarget, rule: ScheduleRule) -> Result<Vec<ScheduleEntry>, String> {
+    SchedulerService::add(&app, target, rule)
+}
+
+#[tauri::command]
+pub async fn remove_schedule(app: AppHandle, id: String) -> Result<Vec<ScheduleEntry>, String> {
+    SchedulerService::remove(&app, &id)
+}
+
+#[tauri::command]
+pub async fn set_schedule_enabled(app: AppHandle, id: String, enabled: bool) -> Result<Vec<ScheduleEntry>, String> {
+    SchedulerService::set_enabled(&app, &id, enabled)
+}
+
+/// The next runs of a rule, or why it is invalid.
+#[tauri::command]
+pub async fn preview_schedule(rule: ScheduleRule) -> Result<Vec<DateTime<Local>>, String> {
+    SchedulerService::preview(&rule)
+}
+
+/// The most recent scheduled jobs, newest first.
+#[tauri::command]
+pub async fn get_schedule_log(app: AppHandle, limit: Option<usize>) -> Result<Vec<JobRecord>, String> {
+    let mut log = SchedulerService::log(&app)?;
+    log.reverse();
+    log.truncate(limit.unwrap_or(50));
+    Ok(log)
+}
@@ -0,0 +1,65 @@
+use tauri::{AppHandle, State};
+use crate::core::backend::{BackendClient, ServerInfo};
+use crate::core::keep_alive::{KeepAliveService, RenewalRecord};
+use crate::core::servers::{AccessResult, ServerEntry, ServerService};
+use crate::state::AppState;
+
+#[tauri::command]
+pub async fn list_servers(state: State<'_, AppState>) -> Result<Vec<ServerEntry>, String> {
+    Ok(state.servers.list())
+}
+
+/// Fails with `ERR_DUPLICATE` for a saved server and `ERR_NOT_FOUND` if the backend does not know it.
+#[tauri::command]
+pub async fn add_server(app: AppHandle, id: String) -> Result<Vec<ServerEntry>, String> {
+    ServerService::add(&app, &id).await
+}
+
+const SEARCH_LIMIT_MAX: usize = 100;
+
+/// Servers the user is entitled to, for autocomplete when adding one.
+#[tauri::command]
+pub async fn search_servers(app: AppHandle, query: String, limit: Option<usize>) -> Result<Vec<ServerInfo>, String> {
+    let (client, token) = BackendClient::authorized(&app).await?;
+    let limit = limit.unwrap_or(20).clamp(1, SEARCH_LIMIT_MAX);
+    Ok(client.search(&token, query.trim(), limit).await?)
+}
+
+#[tauri::command]
+pub async fn remove_server(app: AppHandle, id: String) -> Result<Vec<ServerEntry>, String> {
+    ServerService::remove(&app, &id)
+}
+
+#[tauri::command]
+pub async fn request_server_access(app: AppHandle, id: String) -> Result<(), String> {
+    ServerService::request_access(&app, &id).await
+}
+
+#[tauri::command]
+pub async fn set_server_groups(app: AppHandle, id: String, groups: Vec<String>) -> Result<Vec<ServerEntry>, String> {
+    ServerService::set_groups(&app, &id, groups)
+}
+
+#[tauri::command]
+pub async fn list_server_groups(app: AppHandle) -> Result<Vec<String>, String> {
+    Ok(ServerService::groups(&app))
+}
+
+#[tauri::command]
+pub async fn request_group_access(app: AppHandle, group: String) -> Result<Vec<AccessResult>, String> {
+    ServerService::request_group_access(&app, &group).await
+}
+
+#[tauri::command]
+pub async fn set_keep_alive(app: AppHandle, id: String, enabled: bool) -> Result<Vec<ServerEntry>, String> {
+    ServerService::set_keep_alive(&app, &id, enabled)
+}
+
+/// The most recent keep-alive renewals, newest first.
+#[tauri::command]
+pub async fn get_keep_alive_log(app: AppHandle, limit: Option<usize>) -> Result<Vec<RenewalRecord>, String> {
+    let mut log = KeepAliveService::log(&app)?;
+    log.reverse();
+    log.truncate(limit.unwrap_or(50));
+    Ok(log)
+}
@@ -0,0 +1,29 @@
+use serde_json::{Map, Value};
+use tauri::{AppHandle, State};
+use crate::core::policy::PolicySummary;
+use crate::core::settings::{Settings, SettingsService, UI_SETTINGS};
+use crate::state::AppState;
+use crate::ui::events::shortcut::ShortcutFailure;
+
+#[tauri::command]
+pub async fn get_settings(app: AppHandle) -> Result<Settings, String> {
+    SettingsService::load(&app)
+}
+
+/// Replaces the given top-level keys and returns the full, validated settings.
+#[tauri::command]
+pub async fn update_settings(app: AppHandle, patch: Map<String, Value>) -> Result<Settings, String> {
+    SettingsService::update(&app, patch, &UI_SETTINGS)
+}
+
+/// Which settings the admin policy has locked, so the UI can disable them.
+#[tauri::command]
+pub async fn get_policy(state: State<'_, AppState>) -> Result<PolicySummary, String> {
+    Ok(state.policy.summary())
+}
+
+/// Shortcuts that could not be registered, e.g. because another app owns them.
+#[tauri::command]
+pub async fn get_shortcut_failures(state: State<'_, AppState>) -> Result<Vec<ShortcutFailure>, String> {
+    Ok(state.shortcut_failures.lock().unwrap().clone())
+}
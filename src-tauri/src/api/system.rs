@@ -1,4 +1,5 @@
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_autostart::ManagerExt;
 use crate::core::services::autostart;
 use crate::state::AppState;
 use std::sync::atomic::Ordering;
@@ -19,4 +20,34 @@ pub fn fix_autostart_path(_app: AppHandle) {
             Err(e) => eprintln!("Failed to fix autostart: {}", e),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Turns launch-at-login on or off, unless the admin policy pins it.
+#[tauri::command]
+pub async fn set_autostart(app: AppHandle, state: State<'_, AppState>, enabled: bool) -> Result<bool, String> {
+    if state.policy.autostart.is_some() {
+        return Err("Autostart is managed by your administrator".into());
+    }
+
+    let launcher = app.autolaunch();
+    if enabled {
+        launcher.enable().map_err(|e| e.to_string())?;
+        fix_autostart_path(app.clone());
+    } else {
+        launcher.disable().map_err(|e| e.to_string())?;
+    }
+    launcher.is_enabled().map_err(|e| e.to_string())
+}
+
+/// Applies the autostart value from the admin policy, if it sets one.
+pub fn enforce_autostart_policy<R: Runtime>(app: &AppHandle<R>) {
+    let Some(enabled) = app.state::<AppState>().policy.autostart else {
+        return;
+    };
+
+    let launcher = app.autolaunch();
+    let result = if enabled { launcher.enable() } else { launcher.disable() };
+    if let Err(e) = result {
+        eprintln!("[Policy] Failed to apply autostart: {}", e);
+    }
+}
@@ -1,2 +1,4 @@
 pub const MAIN_WINDOW_LABEL: &str = "main";
-pub const AUTOSTART_APP_NAME: &str = "ServeMe"; // <--- Add this
\ No newline at end of file
+pub const TRAY_ID: &str = "main-tray";
+pub const AUTOSTART_APP_NAME: &str = "ServeMe"; // <--- Add this
+pub const LOCAL_API_URL: &str = "http://127.0.0.1:35555";
\ No newline at end of file
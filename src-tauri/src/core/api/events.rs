@@ -0,0 +1,38 @@
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+const EVENT_BUFFER: usize = 32;
+
+/// An event for local API clients, delivered over the `/events` stream.
+#[derive(Debug, Serialize, Clone)]
+pub struct LocalEvent {
+    pub event: String,
+    pub data: Value,
+}
+
+/// Fan-out of app events to `/events` subscribers. Slow subscribers that fall
+/// more than `EVENT_BUFFER` events behind skip the ones they missed.
+pub struct LocalEventBus {
+    sender: broadcast::Sender<LocalEvent>,
+}
+
+impl Default for LocalEventBus {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(EVENT_BUFFER).0,
+        }
+    }
+}
+
+impl LocalEventBus {
+    pub fn publish<T: Serialize>(&self, event: &str, data: &T) {
+        let data = serde_json::to_value(data).unwrap_or(Value::Null);
+        // Sending only fails when nobody is subscribed.
+        let _ = self.sender.send(LocalEvent { event: event.to_string(), data });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<LocalEvent> {
+        self.sender.subscribe()
+    }
+}
@@ -1,2 +1,3 @@
+pub mod events;
 pub mod server;
-pub mod service;
+pub mod service;
\ No newline at end of file
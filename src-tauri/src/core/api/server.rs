@@ -1,8 +1,12 @@
 use std::net::SocketAddr;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
-use tauri::AppHandle;
-use crate::core::api::service::{TokenService, ConfigService};
+use tauri::{AppHandle, Manager, Url};
+use tokio::sync::broadcast::error::RecvError;
+use crate::core::api::service::{TokenService, ConfigService, SessionService};
+use crate::core::profiles::ProfileService;
+use crate::core::servers::ServerService;
+use crate::state::AppState;
 
 pub async fn start_server(handle: AppHandle) {
     let addr = SocketAddr::from(([127, 0, 0, 1], 35555));
@@ -34,17 +38,44 @@ async fn handle_client(socket: &mut tokio::net::TcpStream, handle: AppHandle) {
     };
 
     let request = String::from_utf8_lossy(&buffer[..n]);
-    let path = request.lines().next().unwrap_or("").split_whitespace().nth(1).unwrap_or("");
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("");
+    let target = request_line.next().unwrap_or("");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
 
-    match path {
-        "/token" => handle_get_token(socket).await,
-        "/config" => handle_get_config(socket, &handle).await,
+    match (method, path) {
+        ("GET", "/token") => handle_get_token(socket, &handle).await,
+        ("GET", "/config") => handle_get_config(socket, &handle).await,
+        ("GET", "/profile") => handle_get_profile(socket, &handle).await,
+        ("GET", "/session") => handle_get_session(socket, &handle).await,
+        ("GET", "/events") => handle_events(socket, &handle).await,
+        ("POST", "/extensions/subscribe") if is_local_client(&request) => handle_subscribe(socket, &handle, query).await,
+        ("POST", "/groups/access") if is_local_client(&request) => handle_group_access(socket, &handle, query).await,
+        ("POST", "/extensions/subscribe" | "/groups/access") => send_error(socket, 403, "Forbidden").await,
+        (_, "/token" | "/config" | "/profile" | "/session" | "/events" | "/extensions/subscribe" | "/groups/access") => {
+            send_error(socket, 405, "Method Not Allowed").await
+        }
         _ => send_error(socket, 404, "Not Found").await,
     }
 }
 
-async fn handle_get_token(socket: &mut tokio::net::TcpStream) {
-    match TokenService::get_encrypted_token().await {
+/// Requests that change something must come from a local program (an extension,
+/// a script), not a web page: browsers add `Origin` and `Sec-Fetch-Site` to
+/// cross-site requests, which other clients have no reason to send.
+fn is_local_client(request: &str) -> bool {
+    !request.lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .any(|(name, value)| {
+            let name = name.trim();
+            name.eq_ignore_ascii_case("origin")
+                || (name.eq_ignore_ascii_case("sec-fetch-site") && value.trim() != "none")
+        })
+}
+
+async fn handle_get_token(socket: &mut tokio::net::TcpStream, handle: &AppHandle) {
+    match TokenService::get_encrypted_token(handle).await {
         Ok(res) => send_json(socket, 200, &res).await,
         Err(e) => send_error(socket, 500, &e).await,
     }
@@ -57,6 +88,88 @@ async fn handle_get_config(socket: &mut tokio::net::TcpStream, handle: &AppHandl
     }
 }
 
+async fn handle_get_profile(socket: &mut tokio::net::TcpStream, handle: &AppHandle) {
+    match ProfileService::active(handle) {
+        Ok(res) => send_json(socket, 200, &res).await,
+        Err(e) => send_error(socket, 500, &e).await,
+    }
+}
+
+async fn handle_get_session(socket: &mut tokio::net::TcpStream, handle: &AppHandle) {
+    match SessionService::get_session_info(handle).await {
+        Ok(res) => send_json(socket, 200, &res).await,
+        Err(e) => send_error(socket, 500, &e).await,
+    }
+}
+
+/// Server-sent events stream (`settings-changed`, ...). Runs until the client disconnects.
+async fn handle_events(socket: &mut tokio::net::TcpStream, handle: &AppHandle) {
+    let mut events = handle.state::<AppState>().events.subscribe();
+
+    let header = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n";
+    if socket.write_all(header.as_bytes()).await.is_err() {
+        return;
+    }
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        };
+        let data = serde_json::to_string(&event.data).unwrap_or_default();
+        let chunk = format!("event: {}\ndata: {}\n\n", event.event, data);
+        if socket.write_all(chunk.as_bytes()).await.is_err() || socket.flush().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// `POST /extensions/subscribe?id=<extension id>&keys=server_url,proxy_url`: a running
+/// extension asks to be restarted when any of the given settings keys change.
+async fn handle_subscribe(socket: &mut tokio::net::TcpStream, handle: &AppHandle, query: &str) {
+    let Ok(url) = Url::parse(&format!("http://localhost/?{}", query)) else {
+        send_error(socket, 400, "Bad Request").await;
+        return;
+    };
+
+    let mut id = String::new();
+    let mut keys = Vec::new();
+    for (name, value) in url.query_pairs() {
+        match name.as_ref() {
+            "id" => id = value.into_owned(),
+            "keys" => keys.extend(value.split(',').map(str::trim).filter(|k| !k.is_empty()).map(String::from)),
+            _ => {}
+        }
+    }
+
+    let state = handle.state::<AppState>();
+    if !state.running_extensions.lock().unwrap().contains_key(&id) {
+        send_error(socket, 404, "Extension is not running").await;
+        return;
+    }
+
+    state.extension_subscriptions.lock().unwrap().insert(id.clone(), keys.clone());
+    send_json(socket, 200, &serde_json::json!({ "id": id, "keys": keys })).await;
+}
+
+/// `POST /groups/access?group=<name>`: requests access to every server in a group and
+/// returns one result per server.
+async fn handle_group_access(socket: &mut tokio::net::TcpStream, handle: &AppHandle, query: &str) {
+    let group = Url::parse(&format!("http://localhost/?{}", query))
+        .ok()
+        .and_then(|url| url.query_pairs().find(|(name, _)| name == "group").map(|(_, value)| value.into_owned()));
+    let Some(group) = group else {
+        send_error(socket, 400, "Bad Request").await;
+        return;
+    };
+
+    match ServerService::request_group_access(handle, &group).await {
+        Ok(results) => send_json(socket, 200, &results).await,
+        Err(e) => send_error(socket, 404, &e).await,
+    }
+}
+
 async fn send_json<T: serde::Serialize>(socket: &mut tokio::net::TcpStream, status: u16, data: &T) {
     let body = serde_json::to_string(data).unwrap_or_default();
     send_response(socket, status, "application/json", &body).await;
@@ -70,7 +183,10 @@ async fn send_error(socket: &mut tokio::net::TcpStream, status: u16, message: &s
 async fn send_response(socket: &mut tokio::net::TcpStream, status: u16, content_type: &str, body: &str) {
     let status_text = match status {
         200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
         404 => "Not Found",
+        405 => "Method Not Allowed",
         500 => "Internal Server Error",
         _ => "Unknown",
     };
@@ -83,3 +199,21 @@ async fn send_response(socket: &mut tokio::net::TcpStream, status: u16, content_
     let _ = socket.write_all(response.as_bytes()).await;
     let _ = socket.flush().await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_clients_send_no_browser_headers() {
+        assert!(is_local_client("POST /groups/access?group=prod HTTP/1.1\r\nHost: 127.0.0.1:35555\r\n\r\n"));
+        assert!(is_local_client("POST /groups/access HTTP/1.1\r\nSec-Fetch-Site: none\r\n\r\n"));
+    }
+
+    #[test]
+    fn browser_requests_are_rejected() {
+        assert!(!is_local_client("POST /groups/access HTTP/1.1\r\nOrigin: https://example.com\r\n\r\n"));
+        assert!(!is_local_client("POST /groups/access HTTP/1.1\r\norigin: null\r\n\r\n"));
+        assert!(!is_local_client("POST /groups/access HTTP/1.1\r\nSec-Fetch-Site: cross-site\r\n\r\n"));
+    }
+}
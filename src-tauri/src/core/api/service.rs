@@ -1,3 +1,4 @@
+use chrono::Utc;
 use serde::Serialize;
 use aes_gcm::{
     aead::{Aead, KeyInit},
@@ -5,8 +6,11 @@ use aes_gcm::{
 };
 use base64::{engine::general_purpose, Engine as _};
 use rand::{rng, Rng};
-use tauri::{AppHandle, Manager};
-use crate::core::keychain::KeychainService;
+use tauri::AppHandle;
+use crate::core::jwt::TokenVerifier;
+use crate::core::keychain::{KeychainService, LoginMethod};
+use crate::core::profiles::ProfileService;
+use crate::core::settings::SettingsService;
 
 #[derive(Serialize)]
 pub struct TokenResponse {
@@ -17,16 +21,33 @@ pub struct TokenResponse {
 #[derive(Serialize)]
 pub struct ConfigResponse {
     pub server_url: String,
+    pub profile: String,
+}
+
+/// Parsed view of the current session. Deliberately carries no token material.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub profile: String,
+    pub method: LoginMethod,
+    pub subject: Option<String>,
+    pub username: Option<String>,
+    pub roles: Vec<String>,
+    pub groups: Vec<String>,
+    pub issued_at: Option<i64>,
+    pub expires_at: i64,
+    pub expires_in: i64,
 }
 
 pub struct TokenService;
 
 impl TokenService {
-    pub async fn get_encrypted_token() -> Result<TokenResponse, String> {
+    pub async fn get_encrypted_token(handle: &AppHandle) -> Result<TokenResponse, String> {
         let master_key_bytes = KeychainService::get_or_create_master_key()?;
         
-        let session = KeychainService::get_session()
+        let session = KeychainService::get_session(&ProfileService::active_name(handle))
             .map_err(|_| "No active session found. Please login.".to_string())?;
+        TokenVerifier::verify(handle, &session.access_token).await?;
         
         let key = Aes256Gcm::new_from_slice(&master_key_bytes)
             .map_err(|e| format!("Encryption key error: {}", e))?;
@@ -50,21 +71,36 @@ pub struct ConfigService;
 
 impl ConfigService {
     pub fn get_config(handle: &AppHandle) -> Result<ConfigResponse, String> {
-        let path = handle.path().app_config_dir()
-            .map_err(|e| e.to_string())?
-            .join("settings.json");
+        let settings = SettingsService::load(handle)?;
 
-        let content = std::fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read config: {}", e))?;
+        Ok(ConfigResponse {
+            server_url: settings.server_url,
+            profile: settings.active_profile,
+        })
+    }
+}
+
+pub struct SessionService;
 
-        let json: serde_json::Value = serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse config: {}", e))?;
+impl SessionService {
+    pub async fn get_session_info(handle: &AppHandle) -> Result<SessionInfo, String> {
+        let profile = ProfileService::active_name(handle);
+        let session = KeychainService::get_session(&profile)
+            .map_err(|_| "No active session found. Please login.".to_string())?;
 
-        let server_url = json.get("server_url")
-            .and_then(|v| v.as_str())
-            .ok_or("server_url not found in config")?
-            .to_string();
+        let claims = TokenVerifier::verify(handle, &session.access_token).await?;
+        let expires_in = (claims.exp - Utc::now().timestamp()).max(0);
 
-        Ok(ConfigResponse { server_url })
+        Ok(SessionInfo {
+            profile,
+            method: session.method,
+            username: claims.display_name(),
+            subject: claims.sub,
+            roles: claims.roles,
+            groups: claims.groups,
+            issued_at: claims.iat,
+            expires_at: claims.exp,
+            expires_in,
+        })
     }
 }
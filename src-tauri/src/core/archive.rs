@@ -0,0 +1,708 @@
+use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use crate::api::extensions::{get_base_name, get_extensions_dir, handle_single_version_constraint, is_valid_extension_item, validate_extension_id};
+use crate::core::backend::validate_server_id;
+use crate::core::profiles::{self, Profile, ProfileService};
+use crate::core::settings::{self, to_map, SavedServer, Settings, SettingsService};
+use crate::state::AppState;
+
+const FORMAT_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SETTINGS_ENTRY: &str = "settings.json";
+const SERVERS_ENTRY: &str = "servers.json";
+const PROFILES_ENTRY: &str = "profiles.json";
+const EXTENSIONS_ENTRY: &str = "extensions";
+/// Inside the extensions directory; hidden, so it is never listed as an extension.
+const STAGING_DIR: &str = ".import";
+
+/// Settings an archive may carry: the UI-owned keys (`settings::UI_SETTINGS`)
+/// without the identity and the extensions switch. TLS, proxy, token
+/// verification and schedules are only changed through their own commands.
+const IMPORTABLE_KEYS: [&str; 7] = [
+    "expiry_notifications",
+    "expiry_warning_minutes",
+    "keep_alive_daily_limit",
+    "keep_alive_hours",
+    "keep_alive_weekdays_only",
+    "dashboard_shortcut",
+    "server_shortcuts",
+];
+
+/// Keys that travel elsewhere in the archive (the active profile in
+/// `profiles.json`, servers in `servers.json`) or not at all, so an older
+/// archive that still has them does not list them as blocked.
+const EXCLUDED_KEYS: [&str; 10] = [
+    "schema_version",
+    "server_url",
+    "username",
+    "oidc_issuer",
+    "oidc_client_id",
+    "active_profile",
+    "profiles",
+    "saved_servers",
+    "tls_ca_bundle",
+    "tls_client_cert",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    format_version: u32,
+    app_version: String,
+    created_at: i64,
+    extensions: Vec<String>,
+}
+
+/// What to do when an imported item differs from one that already exists.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    #[default]
+    Keep,
+    Replace,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Setting,
+    Profile,
+    Server,
+    Extension,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    /// New, or replacing a value that was never changed from the default.
+    Add,
+    /// Conflicts with an existing item, which is overwritten.
+    Replace,
+    /// Conflicts with an existing item, which is left as is.
+    Keep,
+    Unchanged,
+    /// Refused by policy, settings or a running process.
+    Blocked,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportItem {
+    pub kind: ItemKind,
+    pub name: String,
+    pub action: ImportAction,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub applied: bool,
+    pub items: Vec<ImportItem>,
+}
+
+impl ImportItem {
+    fn new(kind: ItemKind, name: &str, action: ImportAction) -> Self {
+        Self { kind, name: name.to_string(), action, reason: None }
+    }
+
+    fn blocked(kind: ItemKind, name: &str, reason: &str) -> Self {
+        Self { reason: Some(reason.to_string()), ..Self::new(kind, name, ImportAction::Blocked) }
+    }
+
+    fn conflict(kind: ItemKind, name: &str, policy: ConflictPolicy) -> Self {
+        let action = match policy {
+            ConflictPolicy::Keep => ImportAction::Keep,
+            ConflictPolicy::Replace => ImportAction::Replace,
+        };
+        Self::new(kind, name, action)
+    }
+
+    fn is_applied(&self) -> bool {
+        matches!(self.action, ImportAction::Add | ImportAction::Replace)
+    }
+}
+
+/// One regular file of an extension, relative to the extension itself
+/// (empty for single-file extensions).
+struct ExtensionFile {
+    path: PathBuf,
+    data: Vec<u8>,
+    mode: u32,
+}
+
+#[derive(Default)]
+struct Bundle {
+    settings: Map<String, Value>,
+    profiles: Vec<Profile>,
+    servers: Vec<SavedServer>,
+    extensions: BTreeMap<String, Vec<ExtensionFile>>,
+}
+
+/// Exports and imports the whole app configuration as a `.tar.gz` archive:
+/// settings, saved servers, profiles and installed extensions.
+///
+/// Secrets are never part of the archive. Sessions, the TLS client key and
+/// proxy credentials live in the keychain, which is not touched here.
+pub struct ArchiveService;
+
+impl ArchiveService {
+    pub fn export<R: Runtime>(app: &AppHandle<R>, path: &Path) -> Result<(), String> {
+        let settings = SettingsService::load(app)?;
+        let mut json = to_map(&settings)?;
+        json.retain(|key, _| IMPORTABLE_KEYS.contains(&key.as_str()));
+
+        let extensions_dir = get_extensions_dir(app)?;
+        let extensions = list_extensions(&extensions_dir)?;
+        let manifest = Manifest {
+            format_version: FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: Utc::now().timestamp(),
+            extensions: extensions.clone(),
+        };
+
+        let file = File::create(path).map_err(|e| format!("Failed to create archive: {}", e))?;
+        let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+        append_json(&mut tar, MANIFEST_ENTRY, &manifest)?;
+        append_json(&mut tar, SETTINGS_ENTRY, &json)?;
+        append_json(&mut tar, SERVERS_ENTRY, &settings.saved_servers)?;
+        append_json(&mut tar, PROFILES_ENTRY, &ProfileService::list(app)?)?;
+
+        for id in &extensions {
+            let source = extensions_dir.join(id);
+            let name = Path::new(EXTENSIONS_ENTRY).join(id);
+            let result = if source.is_dir() {
+                tar.append_dir_all(&name, &source)
+            } else {
+                tar.append_path_with_name(&source, &name)
+            };
+            result.map_err(|e| format!("Failed to add extension '{}': {}", id, e))?;
+        }
+
+        tar.into_inner()
+            .and_then(|gz| gz.finish())
+            .and_then(|mut file| file.flush())
+            .map_err(|e| format!("Failed to write archive: {}", e))
+    }
+
+    /// Compares the archive with the current configuration and, unless
+    /// `dry_run` is set, applies every item marked `add` or `replace`.
+    pub fn import<R: Runtime>(
+        app: &AppHandle<R>,
+        path: &Path,
+        dry_run: bool,
+        policy: ConflictPolicy,
+    ) -> Result<ImportReport, String> {
+        let bundle = read_bundle(path)?;
+        let current = SettingsService::load(app)?;
+        let state = app.state::<AppState>();
+
+        let mut items = plan_settings(&state, &current, &bundle.settings, policy)?;
+        items.extend(plan_profiles(app, &bundle.profiles, policy)?);
+        items.extend(plan_servers(&current, &bundle.servers));
+
+        items.extend(plan_extensions(app, &state, &bundle.extensions, current.extensions_enabled, policy)?);
+
+        // A preview must fail where the import would.
+        merge_settings(&current, &bundle, &items)?.validate()?;
+        if dry_run {
+            return Ok(ImportReport { applied: false, items });
+        }
+
+        // Extensions are written aside first and only moved into place once the
+        // settings are saved, so a failure on either side leaves both unchanged.
+        let staged = stage_extensions(app, &bundle.extensions, &items)?;
+        let saved = SettingsService::modify(app, |settings| {
+            *settings = merge_settings(settings, &bundle, &items)?;
+            Ok(())
+        });
+        if let Err(e) = saved {
+            staged.discard();
+            return Err(e);
+        }
+        staged.commit()?;
+
+        Ok(ImportReport { applied: true, items })
+    }
+}
+
+// --- Planning ---
+
+fn plan_settings(
+    state: &AppState,
+    current: &Settings,
+    imported: &Map<String, Value>,
+    policy: ConflictPolicy,
+) -> Result<Vec<ImportItem>, String> {
+    let current = to_map(current)?;
+    let defaults = to_map(&Settings::default())?;
+    let mut items = Vec::new();
+
+    for (key, value) in imported {
+        if EXCLUDED_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+
+        let item = if current.get(key) == Some(value) {
+            ImportItem::new(ItemKind::Setting, key, ImportAction::Unchanged)
+        } else if !IMPORTABLE_KEYS.contains(&key.as_str()) {
+            ImportItem::blocked(ItemKind::Setting, key, "Not imported; change it in Settings")
+        } else if state.policy.is_locked(key) {
+            ImportItem::blocked(ItemKind::Setting, key, "Managed by your administrator")
+        } else if current.get(key).is_none() || current.get(key) == defaults.get(key) {
+            ImportItem::new(ItemKind::Setting, key, ImportAction::Add)
+        } else {
+            ImportItem::conflict(ItemKind::Setting, key, policy)
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+fn plan_profiles<R: Runtime>(
+    app: &AppHandle<R>,
+    imported: &[Profile],
+    policy: ConflictPolicy,
+) -> Result<Vec<ImportItem>, String> {
+    let existing = ProfileService::list(app)?;
+
+    Ok(imported
+        .iter()
+        .map(|profile| {
+            if let Err(e) = profiles::validate_name(&profile.name) {
+                return ImportItem::blocked(ItemKind::Profile, &profile.name, &e);
+            }
+            match existing.iter().find(|p| p.name == profile.name) {
+                None => ImportItem::new(ItemKind::Profile, &profile.name, ImportAction::Add),
+                Some(p) if p == profile => ImportItem::new(ItemKind::Profile, &profile.name, ImportAction::Unchanged),
+                Some(_) => ImportItem::conflict(ItemKind::Profile, &profile.name, policy),
+            }
+        })
+        .collect())
+}
+
+fn plan_servers(current: &Settings, imported: &[SavedServer]) -> Vec<ImportItem> {
+    imported
+        .iter()
+        .map(|server| {
+            if let Err(e) = validate_server_id(&server.id) {
+                return ImportItem::blocked(ItemKind::Server, &server.id, &e);
+            }
+            let action = if current.saved_servers.iter().any(|s| s.id == server.id) {
+                ImportAction::Unchanged
+            } else {
+                ImportAction::Add
+            };
+            ImportItem::new(ItemKind::Server, &server.id, action)
+        })
+        .collect()
+}
+
+fn plan_extensions<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &AppState,
+    imported: &BTreeMap<String, Vec<ExtensionFile>>,
+    enabled: bool,
+    policy: ConflictPolicy,
+) -> Result<Vec<ImportItem>, String> {
+    let extensions_dir = get_extensions_dir(app)?;
+    let installed = list_extensions(&extensions_dir)?;
+    let running: Vec<String> = state.running_extensions.lock().unwrap().keys().cloned().collect();
+    let mut items = Vec::new();
+
+    for (id, files) in imported {
+        if let Err(e) = validate_extension_id(id) {
+            items.push(ImportItem::blocked(ItemKind::Extension, id, &e));
+            continue;
+        }
+        let base_name = get_base_name(id);
+        if !enabled {
+            items.push(ImportItem::blocked(ItemKind::Extension, id, "Extensions are disabled in settings"));
+            continue;
+        }
+        if let Err(e) = state.policy.check_extension(&base_name) {
+            items.push(ImportItem::blocked(ItemKind::Extension, id, &e));
+            continue;
+        }
+
+        let same_name: Vec<&String> = installed.iter().filter(|i| get_base_name(i) == base_name).collect();
+        let item = if same_name.is_empty() {
+            ImportItem::new(ItemKind::Extension, id, ImportAction::Add)
+        } else if same_name == [id] && is_same_content(&extensions_dir.join(id), files) {
+            ImportItem::new(ItemKind::Extension, id, ImportAction::Unchanged)
+        } else if policy == ConflictPolicy::Replace && same_name.iter().any(|i| running.contains(i)) {
+            ImportItem::blocked(ItemKind::Extension, id, "Stop the running extension first")
+        } else {
+            ImportItem::conflict(ItemKind::Extension, id, policy)
+        };
+        items.push(item);
+    }
+    Ok(items)
+}
+
+// --- Applying ---
+
+/// `current` with every accepted setting, profile and server of the archive.
+fn merge_settings(current: &Settings, bundle: &Bundle, items: &[ImportItem]) -> Result<Settings, String> {
+    let accepted = |kind: ItemKind, name: &str| {
+        items.iter().any(|item| item.kind == kind && item.name == name && item.is_applied())
+    };
+
+    let mut json = to_map(current)?;
+    for (key, value) in &bundle.settings {
+        if accepted(ItemKind::Setting, key) {
+            json.insert(key.clone(), value.clone());
+        }
+    }
+    let mut settings = settings::parse(json)?;
+
+    for profile in bundle.profiles.iter().filter(|p| accepted(ItemKind::Profile, &p.name)) {
+        // The top-level keys always describe the active profile.
+        if profile.name == settings.active_profile {
+            settings.server_url = profile.server_url.clone();
+            settings.username = profile.username.clone();
+            settings.oidc_issuer = profile.oidc_issuer.clone();
+            settings.oidc_client_id = profile.oidc_client_id.clone();
+        }
+        match settings.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile.clone(),
+            None => settings.profiles.push(profile.clone()),
+        }
+    }
+
+    for server in bundle.servers.iter().filter(|s| accepted(ItemKind::Server, &s.id)) {
+        settings.saved_servers.push(server.clone());
+    }
+    Ok(settings)
+}
+
+/// Imported extensions written to a hidden directory next to the installed ones.
+struct StagedExtensions {
+    dir: PathBuf,
+    extensions_dir: PathBuf,
+    ids: Vec<String>,
+}
+
+impl StagedExtensions {
+    /// Replaces other versions of each extension with the staged one.
+    fn commit(self) -> Result<(), String> {
+        for id in &self.ids {
+            handle_single_version_constraint(id, &self.extensions_dir)?;
+            fs::rename(self.dir.join(id), self.extensions_dir.join(id))
+                .map_err(|e| format!("Failed to install extension '{}': {}", id, e))?;
+        }
+        self.discard();
+        Ok(())
+    }
+
+    fn discard(&self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn stage_extensions<R: Runtime>(
+    app: &AppHandle<R>,
+    extensions: &BTreeMap<String, Vec<ExtensionFile>>,
+    items: &[ImportItem],
+) -> Result<StagedExtensions, String> {
+    let extensions_dir = get_extensions_dir(app)?;
+    let staged = StagedExtensions {
+        dir: extensions_dir.join(STAGING_DIR),
+        extensions_dir,
+        ids: items.iter()
+            .filter(|i| i.kind == ItemKind::Extension && i.is_applied() && extensions.contains_key(&i.name))
+            .map(|i| i.name.clone())
+            .collect(),
+    };
+    // Left over from an import that did not finish.
+    staged.discard();
+
+    let result = write_extensions(&staged, extensions);
+    if result.is_err() {
+        staged.discard();
+    }
+    result.map(|_| staged)
+}
+
+fn write_extensions(staged: &StagedExtensions, extensions: &BTreeMap<String, Vec<ExtensionFile>>) -> Result<(), String> {
+    fs::create_dir_all(&staged.dir).map_err(|e| format!("Failed to create extensions directory: {}", e))?;
+
+    for id in &staged.ids {
+        let target = staged.dir.join(id);
+        for file in &extensions[id] {
+            let path = if file.path.as_os_str().is_empty() { target.clone() } else { target.join(&file.path) };
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(&path, &file.data)
+                .map_err(|e| format!("Failed to write extension '{}': {}", id, e))?;
+
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                // Single-file extensions are executables, whatever system the archive came from.
+                let mode = if file.path.as_os_str().is_empty() { 0o755 } else { file.mode & 0o755 };
+                fs::set_permissions(&path, fs::Permissions::from_mode(mode))
+                    .map_err(|e| format!("Failed to set permissions: {}", e))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// --- Archive I/O ---
+
+fn read_bundle(path: &Path) -> Result<Bundle, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut bundle = Bundle::default();
+    let mut manifest: Option<Manifest> = None;
+
+    let entries = archive.entries().map_err(|e| format!("Invalid archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Invalid archive: {}", e))?;
+        let entry_path = entry.path().map_err(|e| format!("Invalid archive: {}", e))?.into_owned();
+        let parts = safe_components(&entry_path)?;
+
+        // Directories are recreated from the file paths; links and devices are never unpacked.
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let mode = entry.header().mode().unwrap_or(0o644);
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data).map_err(|e| format!("Invalid archive: {}", e))?;
+
+        match parts.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+            [MANIFEST_ENTRY] => manifest = Some(parse_entry(MANIFEST_ENTRY, &data)?),
+            [SETTINGS_ENTRY] => bundle.settings = parse_entry(SETTINGS_ENTRY, &data)?,
+            [SERVERS_ENTRY] => bundle.servers = parse_entry(SERVERS_ENTRY, &data)?,
+            [PROFILES_ENTRY] => bundle.profiles = parse_entry(PROFILES_ENTRY, &data)?,
+            [EXTENSIONS_ENTRY, id, rest @ ..] => {
+                bundle.extensions.entry(id.to_string()).or_default().push(ExtensionFile {
+                    path: rest.iter().collect(),
+                    data,
+                    mode,
+                });
+            }
+            _ => eprintln!("[Archive] Skipping unknown entry {}", entry_path.display()),
+        }
+    }
+
+    let manifest = manifest.ok_or("Not a ServeMe configuration archive")?;
+    if manifest.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "The archive was created by a newer version of ServeMe ({}); please update first",
+            manifest.app_version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// Rejects absolute paths and `..`, so nothing can be written outside the extensions directory.
+fn safe_components(path: &Path) -> Result<Vec<String>, String> {
+    path.components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .map(|c| match c {
+            Component::Normal(part) => part.to_str().map(String::from).ok_or_else(|| "Invalid file name in archive".to_string()),
+            _ => Err(format!("Refusing unsafe path in archive: {}", path.display())),
+        })
+        .collect()
+}
+
+fn parse_entry<T: serde::de::DeserializeOwned>(name: &str, data: &[u8]) -> Result<T, String> {
+    serde_json::from_slice(data).map_err(|e| format!("Invalid {} in archive: {}", name, e))
+}
+
+fn append_json<W: Write, T: Serialize>(tar: &mut tar::Builder<W>, name: &str, value: &T) -> Result<(), String> {
+    let data = serde_json::to_vec_pretty(value).map_err(|e| e.to_string())?;
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+
+    tar.append_data(&mut header, name, data.as_slice())
+        .map_err(|e| format!("Failed to write archive: {}", e))
+}
+
+fn list_extensions(dir: &Path) -> Result<Vec<String>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if !is_valid_extension_item(&path) {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            ids.push(name.to_string());
+        }
+    }
+    ids.sort();
+    Ok(ids)
+}
+
+/// Single files are compared by hash; bundles by the hash of every file in them.
+fn is_same_content(installed: &Path, files: &[ExtensionFile]) -> bool {
+    files.iter().all(|file| {
+        let path = if file.path.as_os_str().is_empty() { installed.to_path_buf() } else { installed.join(&file.path) };
+        fs::read(&path).is_ok_and(|data| Sha256::digest(&data) == Sha256::digest(&file.data))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::DEFAULT_REFRESH_INTERVAL_SECS;
+    use serde_json::json;
+
+    fn server(id: &str) -> SavedServer {
+        SavedServer { id: id.into(), keep_alive: false, groups: Vec::new() }
+    }
+
+    fn manifest(format_version: u32) -> Manifest {
+        Manifest { format_version, app_version: "9.9.9".into(), created_at: 0, extensions: Vec::new() }
+    }
+
+    /// Writes a gzipped tar with the given manifest and raw entries to a temp file.
+    fn write_archive(name: &str, manifest: Option<Manifest>, entries: &[(&str, &[u8])]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("serveme-archive-{}-{}.tar.gz", name, std::process::id()));
+        let mut tar = tar::Builder::new(GzEncoder::new(File::create(&path).unwrap(), Compression::default()));
+        if let Some(manifest) = manifest {
+            append_json(&mut tar, MANIFEST_ENTRY, &manifest).unwrap();
+        }
+        for (entry, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o755);
+            tar.append_data(&mut header, entry, *data).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn servers_with_invalid_ids_are_blocked() {
+        let mut current = Settings::default();
+        current.saved_servers.push(server("web-01"));
+
+        let items = plan_servers(&current, &[server("web-01"), server("db-01"), server("../users")]);
+        let actions: Vec<ImportAction> = items.iter().map(|i| i.action).collect();
+        assert_eq!(actions, [ImportAction::Unchanged, ImportAction::Add, ImportAction::Blocked]);
+    }
+
+    #[test]
+    fn merged_settings_only_take_accepted_items() {
+        let bundle = Bundle {
+            settings: json!({ "refresh_interval_secs": 5, "keep_alive_daily_limit": 3 }).as_object().unwrap().clone(),
+            servers: vec![server("db-01")],
+            ..Bundle::default()
+        };
+        let mut items = vec![
+            ImportItem::new(ItemKind::Setting, "refresh_interval_secs", ImportAction::Keep),
+            ImportItem::new(ItemKind::Setting, "keep_alive_daily_limit", ImportAction::Add),
+            ImportItem::new(ItemKind::Server, "db-01", ImportAction::Add),
+        ];
+
+        let merged = merge_settings(&Settings::default(), &bundle, &items).unwrap();
+        assert_eq!(merged.refresh_interval_secs, DEFAULT_REFRESH_INTERVAL_SECS);
+        assert_eq!(merged.keep_alive_daily_limit, 3);
+        assert_eq!(merged.saved_servers, [server("db-01")]);
+        assert!(merged.validate().is_ok());
+
+        // Out of range, so the preview has to fail like the import would.
+        items[0].action = ImportAction::Replace;
+        assert!(merge_settings(&Settings::default(), &bundle, &items).unwrap().validate().is_err());
+    }
+
+    #[test]
+    fn bundles_are_read_back() {
+        let path = write_archive(
+            "read",
+            Some(manifest(FORMAT_VERSION)),
+            &[
+                (SETTINGS_ENTRY, br#"{ "keep_alive_daily_limit": 3 }"#),
+                (SERVERS_ENTRY, br#"[{ "id": "web-01" }]"#),
+                ("extensions/tunnel/run.sh", b"#!/bin/sh"),
+                ("extensions/tunnel/lib/util.sh", b"echo"),
+                ("notes.txt", b"ignored"),
+            ],
+        );
+        let bundle = read_bundle(&path);
+        fs::remove_file(&path).unwrap();
+        let bundle = bundle.unwrap();
+
+        assert_eq!(bundle.settings["keep_alive_daily_limit"], json!(3));
+        assert_eq!(bundle.servers, [server("web-01")]);
+        let files: Vec<&Path> = bundle.extensions["tunnel"].iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(files, [Path::new("run.sh"), Path::new("lib/util.sh")]);
+        assert_eq!(bundle.extensions["tunnel"][0].mode, 0o755);
+    }
+
+    #[test]
+    fn archives_without_a_manifest_are_rejected() {
+        let path = write_archive("foreign", None, &[(SETTINGS_ENTRY, b"{}")]);
+        let result = read_bundle(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn archives_from_newer_versions_are_rejected() {
+        let path = write_archive("newer", Some(manifest(FORMAT_VERSION + 1)), &[]);
+        let result = read_bundle(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.unwrap_err().contains("9.9.9"));
+    }
+
+    #[test]
+    fn unsafe_paths_are_refused() {
+        assert_eq!(safe_components(Path::new("./extensions/a/run.sh")).unwrap(), ["extensions", "a", "run.sh"]);
+        assert!(safe_components(Path::new("extensions/../../.bashrc")).is_err());
+        assert!(safe_components(Path::new("/etc/passwd")).is_err());
+    }
+
+    #[test]
+    fn settings_are_planned_against_current_values() {
+        let state = AppState::new();
+        let current = Settings { keep_alive_daily_limit: 7, ..Settings::default() };
+        let imported = json!({
+            "server_url": "https://elsewhere.example",
+            "jwks_url": "https://evil.example/jwks.json",
+            "extensions_enabled": !current.extensions_enabled,
+            "keep_alive_daily_limit": 3,
+            "keep_alive_weekdays_only": current.keep_alive_weekdays_only,
+            "expiry_notifications": !current.expiry_notifications
+        });
+
+        let plan = |policy| {
+            let items = plan_settings(&state, &current, imported.as_object().unwrap(), policy).unwrap();
+            items.into_iter().map(|i| (i.name, i.action)).collect::<Vec<_>>()
+        };
+
+        // server_url travels with the profile; security settings are never imported.
+        assert_eq!(
+            plan(ConflictPolicy::Replace),
+            [
+                ("expiry_notifications".to_string(), ImportAction::Add),
+                ("extensions_enabled".to_string(), ImportAction::Blocked),
+                ("jwks_url".to_string(), ImportAction::Blocked),
+                ("keep_alive_daily_limit".to_string(), ImportAction::Replace),
+                ("keep_alive_weekdays_only".to_string(), ImportAction::Unchanged),
+            ]
+        );
+        assert_eq!(plan(ConflictPolicy::Keep)[3].1, ImportAction::Keep);
+    }
+}
@@ -0,0 +1,407 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use crate::core::jwt::TokenVerifier;
+use crate::core::keychain::{KeychainService, ProxyCredentials};
+use crate::core::profiles::ProfileService;
+use crate::core::proxy::{self, ProxySettings};
+use crate::core::settings::SettingsService;
+use crate::core::tls::{self, TlsSettings};
+use crate::state::AppState;
+
+pub const API_PREFIX: &str = "/api/v1";
+
+const USER_AGENT: &str = concat!("ServeMe/", env!("CARGO_PKG_VERSION"));
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Everything but unreserved characters (RFC 3986), so `/`, `?`, `#` and `%`
+/// cannot leave a path segment.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Backend failures, rendered as the same `ERR_*` codes the frontend's
+/// `mapBackendError` understands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BackendError {
+    Auth,
+    Forbidden,
+    NotFound,
+    RateLimited,
+    Server,
+    Offline,
+    Network,
+    Unknown(u16),
+    Invalid(String),
+}
+
+impl BackendError {
+    fn from_status(status: u16) -> Self {
+        match status {
+            401 => BackendError::Auth,
+            403 => BackendError::Forbidden,
+            404 => BackendError::NotFound,
+            429 => BackendError::RateLimited,
+            500 => BackendError::Server,
+            503 => BackendError::Offline,
+            other => BackendError::Unknown(other),
+        }
+    }
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::Auth => write!(f, "ERR_AUTH"),
+            BackendError::Forbidden => write!(f, "ERR_FORBIDDEN"),
+            BackendError::NotFound => write!(f, "ERR_NOT_FOUND"),
+            BackendError::RateLimited => write!(f, "ERR_RATE_LIMIT"),
+            BackendError::Server => write!(f, "ERR_SERVER"),
+            BackendError::Offline => write!(f, "ERR_OFFLINE"),
+            BackendError::Network => write!(f, "ERR_NETWORK"),
+            BackendError::Unknown(status) => write!(f, "ERR_UNKNOWN:{}", status),
+            BackendError::Invalid(msg) => write!(f, "ERR_UNKNOWN:{}", msg),
+        }
+    }
+}
+
+impl From<BackendError> for String {
+    fn from(err: BackendError) -> Self {
+        err.to_string()
+    }
+}
+
+impl From<reqwest::Error> for BackendError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_decode() {
+            BackendError::Invalid(err.to_string())
+        } else {
+            BackendError::Network
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AccessStatus {
+    pub server: String,
+    pub ip: Option<String>,
+    pub is_active: bool,
+    pub expiration: Option<i64>,
+    pub time_remaining: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExistsResponse {
+    exists: bool,
+}
+
+/// A server the user is entitled to, as listed by `/users/servers`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ServerInfo {
+    pub id: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub environment: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ServerListResponse {
+    servers: Vec<ServerInfo>,
+}
+
+/// The pooled HTTP client shared by every Rust call to the backend and the
+/// identity provider. Built on first use and dropped by `reset_http_client`
+/// when the network settings change.
+#[derive(Default)]
+pub struct HttpClientCache {
+    client: RwLock<Option<reqwest::Client>>,
+}
+
+pub fn http_client<R: Runtime>(app: &AppHandle<R>) -> Result<reqwest::Client, String> {
+    let state = app.state::<AppState>();
+    if let Some(client) = state.http.client.read().unwrap().as_ref() {
+        return Ok(client.clone());
+    }
+
+    let proxy_settings = ProxySettings::load(app)?;
+    let credentials = KeychainService::get_proxy_credentials()?;
+    let client = build_client(&TlsSettings::load(app)?, &proxy_settings, credentials.as_ref())?;
+    *state.http.client.write().unwrap() = Some(client.clone());
+    Ok(client)
+}
+
+pub fn reset_http_client<R: Runtime>(app: &AppHandle<R>) {
+    *app.state::<AppState>().http.client.write().unwrap() = None;
+}
+
+/// Builds a client from explicit settings, so new settings can be tried before they are saved.
+pub fn build_client(
+    tls: &TlsSettings,
+    proxy: &ProxySettings,
+    proxy_credentials: Option<&ProxyCredentials>,
+) -> Result<reqwest::Client, String> {
+    let builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT);
+
+    let client_key = KeychainService::get_client_key()?;
+    let builder = tls::apply(builder, tls, client_key.as_deref())?;
+    proxy::apply(builder, proxy, proxy_credentials)?
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+pub fn active_server_url<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    SettingsService::load(app)
+        .ok()
+        .map(|s| s.server_url)
+        .filter(|s| !s.is_empty())
+}
+
+/// Server ids end up in URL paths, so ids from outside the UI (command line,
+/// links) are limited to hostname characters.
+pub fn validate_server_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && id.len() <= 253
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !id.starts_with('.');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not a valid server id", id))
+    }
+}
+
+/// Escapes a value for use as one path segment. Ids are validated where they
+/// enter the app; this keeps anything that slipped through inside its segment.
+fn encode_segment(value: &str) -> String {
+    utf8_percent_encode(value, PATH_SEGMENT).to_string()
+}
+
+/// Typed access to the ServeMe backend REST API.
+pub struct BackendClient {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl BackendClient {
+    /// Uses the `server_url` of the active profile.
+    pub fn new<R: Runtime>(app: &AppHandle<R>) -> Result<Self, BackendError> {
+        let base_url = active_server_url(app).ok_or(BackendError::Offline)?;
+        Self::with_base_url(app, &base_url)
+    }
+
+    pub fn with_base_url<R: Runtime>(app: &AppHandle<R>, base_url: &str) -> Result<Self, BackendError> {
+        let http = http_client(app).map_err(BackendError::Invalid)?;
+        Ok(Self::with_client(http, base_url))
+    }
+
+    /// Client for the active profile plus its verified access token. No session, or
+    /// one whose token does not verify, is reported as `ERR_AUTH`, the same as a
+    /// token the backend rejected.
+    pub async fn authorized<R: Runtime>(app: &AppHandle<R>) -> Result<(Self, String), BackendError> {
+        let session = TokenVerifier::verified_session(app, &ProfileService::active_name(app))
+            .await
+            .map_err(|_| BackendError::Auth)?;
+        Ok((Self::new(app)?, session.access_token))
+    }
+
+    /// Uses a client that is not (yet) the shared one, e.g. to test unsaved network settings.
+    pub fn with_client(http: reqwest::Client, base_url: &str) -> Self {
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> Result<TokenPair, BackendError> {
+        let body = serde_json::json!({ "username": username, "password": password });
+        self.send_json(self.http.post(self.url("/login")).json(&body)).await
+    }
+
+    pub async fn refresh(&self, username: Option<&str>, refresh_token: &str) -> Result<TokenPair, BackendError> {
+        let body = serde_json::json!({ "username": username, "refresh_token": refresh_token });
+        self.send_json(self.http.post(self.url("/token/refresh")).json(&body)).await
+    }
+
+    pub async fn logout(&self, refresh_token: &str) -> Result<(), BackendError> {
+        let body = serde_json::json!({ "refresh_token": refresh_token });
+        self.send(self.http.post(self.url("/logout")).json(&body)).await.map(|_| ())
+    }
+
+    pub async fn access(&self, token: &str, server: &str) -> Result<(), BackendError> {
+        let body = serde_json::json!({ "server_id": server });
+        let request = self.http.post(self.url("/users/access")).bearer_auth(token).json(&body);
+        self.send(request).await.map(|_| ())
+    }
+
+    pub async fn status(&self, token: &str, server: &str) -> Result<AccessStatus, BackendError> {
+        let path = format!("/users/access/{}/status", encode_segment(server));
+        self.send_json(self.http.get(self.url(&path)).bearer_auth(token)).await
+    }
+
+    pub async fn exists(&self, token: &str, server: &str) -> Result<bool, BackendError> {
+        let path = format!("/users/servers/{}/exists", encode_segment(server));
+        let response: ExistsResponse = self.send_json(self.http.get(self.url(&path)).bearer_auth(token)).await?;
+        Ok(response.exists)
+    }
+
+    /// Servers the user may request, matching `query` by name or metadata; an
+    /// empty query lists them all, up to `limit`.
+    pub async fn search(&self, token: &str, query: &str, limit: usize) -> Result<Vec<ServerInfo>, BackendError> {
+        let limit = limit.to_string();
+        let url = reqwest::Url::parse_with_params(&self.url("/users/servers"), [("search", query), ("limit", limit.as_str())])
+            .map_err(|e| BackendError::Invalid(e.to_string()))?;
+        let response: ServerListResponse = self.send_json(self.http.get(url).bearer_auth(token)).await?;
+        Ok(response.servers)
+    }
+
+    pub async fn health(&self) -> Result<(), BackendError> {
+        let request = self.http.get(self.url("/health")).timeout(HEALTH_TIMEOUT);
+        self.send(request).await.map(|_| ())
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}{}{}", self.base_url, API_PREFIX, endpoint)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, BackendError> {
+        let response = request.send().await?;
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(BackendError::from_status(response.status().as_u16()))
+        }
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T, BackendError> {
+        Ok(self.send(request).await?.json::<T>().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// A backend that answers one request per canned response, in order, and
+    /// hands back the request heads it saw.
+    async fn stub_backend(responses: Vec<(u16, &'static str)>) -> (String, tokio::task::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let requests = tokio::spawn(async move {
+            let mut seen = Vec::new();
+            for (status, body) in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut head = Vec::new();
+                let mut buffer = [0; 4096];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buffer).await.unwrap();
+                    head.extend_from_slice(&buffer[..n]);
+                }
+                seen.push(String::from_utf8_lossy(&head).into_owned());
+
+                let response = format!(
+                    "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, body.len(), body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            seen
+        });
+        (base_url, requests)
+    }
+
+    fn client(base_url: &str) -> BackendClient {
+        BackendClient::with_client(reqwest::Client::builder().no_proxy().build().unwrap(), base_url)
+    }
+
+    #[test]
+    fn server_ids_are_limited_to_hostname_characters() {
+        for id in ["web-01", "db_1.example.com", "A1"] {
+            assert!(validate_server_id(id).is_ok(), "{}", id);
+        }
+        for id in ["", ".hidden", "../admin", "a/b", "a?b", "a#b", "a%2e", "a b", "é"] {
+            assert!(validate_server_id(id).is_err(), "{}", id);
+        }
+        assert!(validate_server_id(&"a".repeat(254)).is_err());
+    }
+
+    #[tokio::test]
+    async fn status_keeps_the_server_id_in_one_segment() {
+        let body = r#"{"server":"x","ip":null,"is_active":false,"expiration":null,"time_remaining":null}"#;
+        let (base_url, requests) = stub_backend(vec![(200, body)]).await;
+
+        client(&base_url).status("token", "../../admin?x=1#").await.unwrap();
+
+        let request = &requests.await.unwrap()[0];
+        assert!(request.starts_with("GET /api/v1/users/access/..%2F..%2Fadmin%3Fx%3D1%23/status "), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn search_sends_query_and_limit_and_maps_metadata() {
+        let body = r#"{"servers":[
+            {"id":"web-01","description":"Public web","environment":"prod","tags":["web","eu"]},
+            {"id":"db-01"}
+        ]}"#;
+        let (base_url, requests) = stub_backend(vec![(200, body)]).await;
+
+        let servers = client(&base_url).search("token", "web & prod", 5).await.unwrap();
+
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].id, "web-01");
+        assert_eq!(servers[0].description.as_deref(), Some("Public web"));
+        assert_eq!(servers[0].environment.as_deref(), Some("prod"));
+        assert_eq!(servers[0].tags, ["web", "eu"]);
+        assert_eq!(servers[1].id, "db-01");
+        assert!(servers[1].description.is_none() && servers[1].environment.is_none() && servers[1].tags.is_empty());
+
+        let request = &requests.await.unwrap()[0];
+        assert!(request.starts_with("GET /api/v1/users/servers?search=web+%26+prod&limit=5 "), "{}", request);
+        assert!(request.to_lowercase().contains("authorization: bearer token"), "{}", request);
+    }
+
+    #[tokio::test]
+    async fn search_reports_backend_errors() {
+        let cases = vec![
+            (401, "", BackendError::Auth),
+            (403, "", BackendError::Forbidden),
+            (429, "", BackendError::RateLimited),
+            (500, "", BackendError::Server),
+            (503, "", BackendError::Offline),
+            (502, "", BackendError::Unknown(502)),
+        ];
+        let (base_url, requests) = stub_backend(cases.iter().map(|(status, body, _)| (*status, *body)).collect()).await;
+
+        let client = client(&base_url);
+        for (status, _, expected) in cases {
+            assert_eq!(client.search("token", "", 20).await.unwrap_err(), expected, "{}", status);
+        }
+        requests.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn search_rejects_malformed_responses() {
+        let (base_url, requests) = stub_backend(vec![(200, r#"{"servers":[{"name":"no id"}]}"#)]).await;
+        let error = client(&base_url).search("token", "", 20).await.unwrap_err();
+        assert!(matches!(error, BackendError::Invalid(_)), "{:?}", error);
+        requests.await.unwrap();
+    }
+}
@@ -0,0 +1,258 @@
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, Url};
+use crate::api::extensions::{get_base_name, get_extensions_dir, validate_extension_id};
+use crate::core::backend::validate_server_id;
+use crate::core::settings::{self, to_map, SettingsService};
+use crate::state::AppState;
+
+pub const SCHEME: &str = "serveme";
+
+/// Settings a link may change. Everything else (TLS, extensions, profiles) has
+/// to be changed in the app itself.
+pub const LINKABLE_SETTINGS: [&str; 5] = [
+    "server_url",
+    "refresh_interval_secs",
+    "proxy_mode",
+    "proxy_url",
+    "proxy_no_proxy",
+];
+
+/// A parsed `serveme://` link.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeepLink {
+    /// Opens a dashboard route, e.g. `serveme://settings`.
+    Open(&'static str),
+    /// Changes something; runs only after the user confirms it in the dashboard.
+    Action(DeepLinkAction),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum DeepLinkAction {
+    /// `serveme://access/<server>`
+    Access { server: String },
+    /// `serveme://servers/add/<server>`
+    AddServer { server: String },
+    /// `serveme://extensions/run/<id>`
+    RunExtension { id: String },
+    /// `serveme://settings?server_url=...`
+    UpdateSettings { patch: Map<String, Value> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingDeepLink {
+    pub id: u64,
+    pub description: String,
+    #[serde(flatten)]
+    pub action: DeepLinkAction,
+}
+
+/// Actions waiting for confirmation. The dashboard only ever sends back an id,
+/// so a script in the webview cannot confirm anything a link did not ask for.
+#[derive(Default)]
+pub struct DeepLinkQueue {
+    next_id: AtomicU64,
+    pending: Mutex<Vec<PendingDeepLink>>,
+}
+
+impl DeepLinkQueue {
+    pub fn push(&self, action: DeepLinkAction) -> PendingDeepLink {
+        let pending = PendingDeepLink {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            description: action.describe(),
+            action,
+        };
+        self.pending.lock().unwrap().push(pending.clone());
+        pending
+    }
+
+    pub fn take(&self, id: u64) -> Option<DeepLinkAction> {
+        let mut pending = self.pending.lock().unwrap();
+        let index = pending.iter().position(|p| p.id == id)?;
+        Some(pending.remove(index).action)
+    }
+
+    pub fn list(&self) -> Vec<PendingDeepLink> {
+        self.pending.lock().unwrap().clone()
+    }
+}
+
+impl DeepLink {
+    pub fn parse(url: &Url) -> Result<Self, String> {
+        if url.scheme() != SCHEME {
+            return Err(format!("Unsupported link scheme '{}'", url.scheme()));
+        }
+
+        let host = url.host_str().unwrap_or_default();
+        let segments: Vec<String> = url
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).map(decode).collect())
+            .unwrap_or_default();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let action = match (host, segments.as_slice()) {
+            ("access", [server]) => DeepLinkAction::Access { server: server.to_string() },
+            ("servers", ["add", server]) => DeepLinkAction::AddServer { server: server.to_string() },
+            ("extensions", ["run", id]) => DeepLinkAction::RunExtension { id: id.to_string() },
+            ("settings", []) => {
+                let patch: Map<String, Value> = url
+                    .query_pairs()
+                    .map(|(key, value)| (key.into_owned(), setting_value(&value)))
+                    .collect();
+                if patch.is_empty() {
+                    return Ok(DeepLink::Open("/settings"));
+                }
+                DeepLinkAction::UpdateSettings { patch }
+            }
+            _ => return Err(format!("Unknown link '{}'", url)),
+        };
+        Ok(DeepLink::Action(action))
+    }
+}
+
+impl DeepLinkAction {
+    /// Checks the action against the current state before the user is asked,
+    /// so the dashboard never offers something that cannot work.
+    pub fn validate<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), String> {
+        let state = app.state::<AppState>();
+
+        match self {
+            DeepLinkAction::Access { server } => validate_server_id(server),
+            DeepLinkAction::AddServer { server } => {
+                validate_server_id(server)?;
+                if SettingsService::load(app)?.saved_servers.iter().any(|s| &s.id == server) {
+                    return Err(format!("Server '{}' is already on the dashboard", server));
+                }
+                Ok(())
+            }
+            DeepLinkAction::RunExtension { id } => {
+                validate_extension_id(id)?;
+                state.policy.check_extension(&get_base_name(id))?;
+                if !SettingsService::load(app)?.extensions_enabled {
+                    return Err("Extensions are disabled in settings".into());
+                }
+                if !get_extensions_dir(app)?.join(id).exists() {
+                    return Err(format!("Extension '{}' not found", id));
+                }
+                Ok(())
+            }
+            DeepLinkAction::UpdateSettings { patch } => {
+                let mut json = to_map(&SettingsService::load(app)?)?;
+                for (key, value) in patch {
+                    if !LINKABLE_SETTINGS.contains(&key.as_str()) {
+                        return Err(format!("'{}' cannot be changed by a link", key));
+                    }
+                    if state.policy.is_locked(key) {
+                        return Err(format!("'{}' is managed by your administrator", key));
+                    }
+                    json.insert(key.clone(), value.clone());
+                }
+                settings::parse(json)?.validate()
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            DeepLinkAction::Access { server } => format!("Request access to '{}'?", server),
+            DeepLinkAction::AddServer { server } => format!("Add server '{}' to the dashboard?", server),
+            DeepLinkAction::RunExtension { id } => format!("Run extension '{}'?", get_base_name(id)),
+            DeepLinkAction::UpdateSettings { patch } => {
+                let changes: Vec<String> = patch.iter().map(|(key, value)| format!("{} = {}", key, value)).collect();
+                format!("Change settings: {}?", changes.join(", "))
+            }
+        }
+    }
+}
+
+fn decode(segment: &str) -> String {
+    percent_decode_str(segment).decode_utf8_lossy().into_owned()
+}
+
+/// Query values are strings; numbers and booleans are passed on as such so
+/// they match the settings types.
+fn setting_value(raw: &str) -> Value {
+    match serde_json::from_str::<Value>(raw) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_))) => value,
+        _ => Value::String(raw.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(link: &str) -> Result<DeepLink, String> {
+        DeepLink::parse(&Url::parse(link).unwrap())
+    }
+
+    #[test]
+    fn parses_server_and_extension_links() {
+        assert_eq!(
+            parse("serveme://access/web-01").unwrap(),
+            DeepLink::Action(DeepLinkAction::Access { server: "web-01".into() })
+        );
+        assert_eq!(
+            parse("serveme://servers/add/db.prod").unwrap(),
+            DeepLink::Action(DeepLinkAction::AddServer { server: "db.prod".into() })
+        );
+        assert_eq!(
+            parse("serveme://extensions/run/tunnel-1.2.0.zip").unwrap(),
+            DeepLink::Action(DeepLinkAction::RunExtension { id: "tunnel-1.2.0.zip".into() })
+        );
+    }
+
+    #[test]
+    fn path_segments_are_percent_decoded() {
+        // Decoded values still go through validate_server_id before anything runs.
+        assert_eq!(
+            parse("serveme://access/web%2F01/").unwrap(),
+            DeepLink::Action(DeepLinkAction::Access { server: "web/01".into() })
+        );
+    }
+
+    #[test]
+    fn settings_links_keep_value_types() {
+        let link = parse("serveme://settings?server_url=https%3A%2F%2Fcorp.example&refresh_interval_secs=120&proxy_mode=none")
+            .unwrap();
+        let DeepLink::Action(DeepLinkAction::UpdateSettings { patch }) = link else {
+            panic!("expected a settings change, got {:?}", link);
+        };
+        assert_eq!(patch["server_url"], json!("https://corp.example"));
+        assert_eq!(patch["refresh_interval_secs"], json!(120));
+        assert_eq!(patch["proxy_mode"], json!("none"));
+    }
+
+    #[test]
+    fn settings_without_a_query_only_open_the_page() {
+        assert_eq!(parse("serveme://settings").unwrap(), DeepLink::Open("/settings"));
+    }
+
+    #[test]
+    fn unknown_links_are_rejected() {
+        assert!(parse("https://access/web-01").is_err());
+        assert!(parse("serveme://access").is_err());
+        assert!(parse("serveme://access/a/b").is_err());
+        assert!(parse("serveme://servers/remove/web-01").is_err());
+        assert!(parse("serveme://admin").is_err());
+    }
+
+    #[test]
+    fn queued_actions_are_taken_once() {
+        let queue = DeepLinkQueue::default();
+        let first = queue.push(DeepLinkAction::Access { server: "web-01".into() });
+        let second = queue.push(DeepLinkAction::AddServer { server: "db".into() });
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.description, "Request access to 'web-01'?");
+
+        assert_eq!(queue.take(first.id), Some(DeepLinkAction::Access { server: "web-01".into() }));
+        assert_eq!(queue.take(first.id), None);
+        assert_eq!(queue.list().len(), 1);
+    }
+}
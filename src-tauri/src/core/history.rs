@@ -0,0 +1,183 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use crate::core::journal::Journal;
+use crate::core::servers::{ServerEntry, ServerStatus};
+use crate::state::AppState;
+
+const LOG: Journal = Journal::new("access_history.jsonl");
+
+/// Records older than this are dropped the first time the history is used after
+/// the app starts. Two years covers an annual report with room to spare.
+const RETENTION_DAYS: i64 = 730;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryEvent {
+    /// An access request was accepted by the backend.
+    Requested,
+    /// The backend reported access where there was none.
+    Granted,
+    /// Access was extended while it was still active.
+    Renewed,
+    Expired,
+    /// An access request was refused or could not be sent.
+    Failed,
+}
+
+/// One line of `access_history.jsonl`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryRecord {
+    pub time: DateTime<Local>,
+    pub server: String,
+    pub event: HistoryEvent,
+    /// The address access was granted to, from the status response.
+    pub ip: Option<String>,
+    /// Epoch seconds, when the backend reported one.
+    pub expiration: Option<i64>,
+    pub detail: Option<String>,
+}
+
+/// Filters for `query` and `export`. Dates are local and inclusive.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryQuery {
+    pub server: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl HistoryQuery {
+    fn matches(&self, record: &HistoryRecord) -> bool {
+        let day = record.time.date_naive();
+        self.server.as_ref().is_none_or(|s| *s == record.server)
+            && self.from.is_none_or(|from| day >= from)
+            && self.to.is_none_or(|to| day <= to)
+    }
+}
+
+/// Expiration of the last grant or renewal recorded per server, so a grant seen
+/// again (e.g. on every start) is not recorded twice. Read from the file once,
+/// after old records were pruned, then kept up to date by `record`.
+#[derive(Default)]
+pub struct AccessHistory {
+    last_grants: Mutex<Option<HashMap<String, Option<i64>>>>,
+}
+
+/// A local record of what was accessed and when, kept for the user's own
+/// compliance reports. Nothing here is sent anywhere.
+pub struct AccessHistoryService;
+
+impl AccessHistoryService {
+    pub fn record<R: Runtime>(app: &AppHandle<R>, entry: &ServerEntry, event: HistoryEvent, detail: Option<String>) {
+        let record = HistoryRecord {
+            time: Local::now(),
+            server: entry.id.clone(),
+            event,
+            ip: entry.ip.clone(),
+            expiration: entry.expiration,
+            detail,
+        };
+
+        // Held while writing, so records are never appended while the file is pruned.
+        let state = app.state::<AppState>();
+        let mut last_grants = state.history.last_grants.lock().unwrap();
+        let last_grants = last_grants.get_or_insert_with(|| load_last_grants(app));
+        if matches!(event, HistoryEvent::Granted | HistoryEvent::Renewed) {
+            last_grants.insert(record.server.clone(), record.expiration);
+        }
+
+        if let Err(e) = LOG.append(app, &record) {
+            eprintln!("[History] Failed to write history: {}", e);
+        }
+    }
+
+    /// Called by the server registry on every status change. Grants, renewals
+    /// and expiries are read off the transition, so they are recorded whichever
+    /// way access was requested (dashboard, keep-alive, scheduler, web).
+    pub fn access_changed<R: Runtime>(app: &AppHandle<R>, previous: &ServerEntry, next: &ServerEntry) {
+        let (entry, event) = match (previous.status, next.status) {
+            (ServerStatus::AccessGranted, ServerStatus::AccessGranted) => {
+                match (previous.minutes_left(), next.minutes_left()) {
+                    (Some(before), Some(after)) if after > before => (next, HistoryEvent::Renewed),
+                    _ => return,
+                }
+            }
+            // Also seen on every start while a grant is active; one entry per grant is enough.
+            (_, ServerStatus::AccessGranted) if Self::already_recorded(app, next) => return,
+            (_, ServerStatus::AccessGranted) => (next, HistoryEvent::Granted),
+            // Only a grant that ran out; going idle earlier means the session
+            // ended (e.g. logout), which says nothing about access on the server.
+            (ServerStatus::AccessGranted, ServerStatus::Idle) if previous.minutes_left().is_some_and(|m| m <= 1) => {
+                (previous, HistoryEvent::Expired)
+            }
+            _ => return,
+        };
+        Self::record(app, entry, event, None);
+    }
+
+    fn already_recorded<R: Runtime>(app: &AppHandle<R>, entry: &ServerEntry) -> bool {
+        let Some(expiration) = entry.expiration else {
+            return false;
+        };
+        let state = app.state::<AppState>();
+        let mut last_grants = state.history.last_grants.lock().unwrap();
+        last_grants.get_or_insert_with(|| load_last_grants(app)).get(&entry.id) == Some(&Some(expiration))
+    }
+
+    /// Matching records, oldest first.
+    pub fn query<R: Runtime>(app: &AppHandle<R>, query: &HistoryQuery) -> Result<Vec<HistoryRecord>, String> {
+        let mut records: Vec<HistoryRecord> = LOG.read(app)?;
+        records.retain(|r| query.matches(r));
+        Ok(records)
+    }
+
+    /// Writes the matching records to `path`, which must come from a save dialog
+    /// the user answered. Returns how many were written.
+    pub fn export<R: Runtime>(app: &AppHandle<R>, query: &HistoryQuery, format: ExportFormat, path: &Path) -> Result<usize, String> {
+        let records = Self::query(app, query)?;
+        let file = File::create(path).map_err(|e| format!("Failed to create export: {}", e))?;
+
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_writer_pretty(file, &records).map_err(|e| format!("Failed to write export: {}", e))?;
+            }
+            ExportFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(file);
+                for record in &records {
+                    writer.serialize(record).map_err(|e| format!("Failed to write export: {}", e))?;
+                }
+                writer.flush().map_err(|e| format!("Failed to write export: {}", e))?;
+            }
+        }
+        Ok(records.len())
+    }
+}
+
+/// Prunes records past `RETENTION_DAYS`, then finds the last grant of each server.
+fn load_last_grants<R: Runtime>(app: &AppHandle<R>) -> HashMap<String, Option<i64>> {
+    let cutoff = Local::now() - Duration::days(RETENTION_DAYS);
+    match LOG.retain(app, |record: &HistoryRecord| record.time >= cutoff) {
+        Ok(0) => {}
+        Ok(dropped) => eprintln!("[History] Dropped {} records older than {} days", dropped, RETENTION_DAYS),
+        Err(e) => eprintln!("[History] Failed to prune history: {}", e),
+    }
+
+    let records: Vec<HistoryRecord> = LOG.read(app).unwrap_or_default();
+    records.into_iter()
+        .filter(|r| matches!(r.event, HistoryEvent::Granted | HistoryEvent::Renewed))
+        .map(|r| (r.server, r.expiration))
+        .collect()
+}
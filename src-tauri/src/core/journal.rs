@@ -0,0 +1,88 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// An append-only JSON Lines file in the app data directory, one record per line.
+pub struct Journal {
+    file: &'static str,
+}
+
+impl Journal {
+    pub const fn new(file: &'static str) -> Self {
+        Self { file }
+    }
+
+    pub fn append<R: Runtime, T: Serialize>(&self, app: &AppHandle<R>, record: &T) -> Result<(), String> {
+        let path = self.path(app)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let mut line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+        line.push('\n');
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| e.to_string())?;
+        file.write_all(line.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    /// All records, oldest first. Lines that do not parse are skipped.
+    pub fn read<R: Runtime, T: DeserializeOwned>(&self, app: &AppHandle<R>) -> Result<Vec<T>, String> {
+        let file = match std::fs::File::open(self.path(app)?) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.file, e)),
+        };
+
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+
+    /// Rewrites the file with only the records `keep` accepts, e.g. to drop old
+    /// ones. Lines that do not parse are dropped as well. Returns how many went.
+    pub fn retain<R: Runtime, T, F>(&self, app: &AppHandle<R>, keep: F) -> Result<usize, String>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Fn(&T) -> bool,
+    {
+        let path = self.path(app)?;
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.file, e)),
+        };
+
+        let mut kept = String::new();
+        let mut dropped = 0;
+        for line in content.lines() {
+            match serde_json::from_str::<T>(line) {
+                Ok(record) if keep(&record) => {
+                    kept.push_str(line);
+                    kept.push('\n');
+                }
+                _ => dropped += 1,
+            }
+        }
+        if dropped == 0 {
+            return Ok(0);
+        }
+
+        // Same as settings.json: a crash mid-write must not truncate the file.
+        let tmp = path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, kept).map_err(|e| format!("Failed to write {}: {}", self.file, e))?;
+        std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write {}: {}", self.file, e))?;
+        Ok(dropped)
+    }
+
+    fn path<R: Runtime>(&self, app: &AppHandle<R>) -> Result<PathBuf, String> {
+        Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join(self.file))
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+use crate::core::journal::Journal;
+use crate::core::keychain::KeychainService;
+use crate::core::profiles::ProfileService;
+use crate::core::servers::{ServerEntry, ServerService, ServerStatus};
+use crate::core::settings::{Settings, SettingsService};
+use crate::state::AppState;
+
+const LOG: Journal = Journal::new("keep_alive.jsonl");
+
+/// Records older than this are dropped the first time the log is counted each
+/// day. The daily limit only needs today; the rest is for troubleshooting.
+const RETENTION_DAYS: i64 = 90;
+
+/// How long before expiry a pinned server is renewed.
+const RENEW_LEAD_MINUTES: i64 = 3;
+/// Pause after a failed attempt, so a broken backend is not hit on every poll.
+const RETRY_AFTER: Duration = Duration::from_secs(60);
+/// A skipped renewal is not reconsidered for the rest of the grant.
+const SKIP_FOR: Duration = Duration::from_secs(RENEW_LEAD_MINUTES as u64 * 60);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RenewalOutcome {
+    Renewed,
+    Failed,
+    Skipped,
+}
+
+/// One line of `keep_alive.jsonl`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenewalRecord {
+    pub time: DateTime<Local>,
+    pub server: String,
+    pub outcome: RenewalOutcome,
+    pub reason: Option<String>,
+}
+
+#[derive(Default)]
+pub struct KeepAlive {
+    /// When each pinned server may be looked at again.
+    next_check: Mutex<HashMap<String, Instant>>,
+    /// Renewals per server on the given day, for the daily limit. Counted from
+    /// the log once, then kept up to date as renewals are recorded.
+    renewed: Mutex<Option<(NaiveDate, HashMap<String, u32>)>>,
+}
+
+impl KeepAlive {
+    fn renewed_on<R: Runtime>(&self, app: &AppHandle<R>, server: &str, day: NaiveDate) -> Result<u32, String> {
+        let mut renewed = self.renewed.lock().unwrap();
+        // First use today: count from the log, which covers earlier runs of the app.
+        if !renewed.as_ref().is_some_and(|(counted, _)| *counted == day) {
+            prune(app);
+            let mut counts = HashMap::new();
+            for record in KeepAliveService::log(app)? {
+                if record.outcome == RenewalOutcome::Renewed && record.time.date_naive() == day {
+                    *counts.entry(record.server).or_default() += 1;
+                }
+            }
+            *renewed = Some((day, counts));
+        }
+        Ok(renewed.as_ref().and_then(|(_, counts)| counts.get(server)).copied().unwrap_or_default())
+    }
+
+    fn count_renewal(&self, server: &str, day: NaiveDate) {
+        if let Some((counted, counts)) = self.renewed.lock().unwrap().as_mut() {
+            if *counted == day {
+                *counts.entry(server.to_string()).or_default() += 1;
+            }
+        }
+    }
+}
+
+pub struct KeepAliveService;
+
+impl KeepAliveService {
+    /// Called by the server registry after every poll, changed or not. Starts a
+    /// renewal for a pinned server whose access is about to run out.
+    pub fn status_polled<R: Runtime>(app: &AppHandle<R>, entry: &ServerEntry) {
+        if !entry.keep_alive || entry.status != ServerStatus::AccessGranted {
+            return;
+        }
+        if !entry.minutes_left().is_some_and(|m| m <= RENEW_LEAD_MINUTES) {
+            return;
+        }
+
+        let state = app.state::<AppState>();
+        {
+            let mut next_check = state.keep_alive.next_check.lock().unwrap();
+            if next_check.get(&entry.id).is_some_and(|at| Instant::now() < *at) {
+                return;
+            }
+            // Nobody to renew for; the registry turns the servers idle shortly.
+            if KeychainService::get_session(&ProfileService::active_name(app)).is_err() {
+                return;
+            }
+            // Claimed before the request starts; the next poll must not start another.
+            next_check.insert(entry.id.clone(), Instant::now() + RETRY_AFTER);
+        }
+
+        let app = app.clone();
+        let server = entry.id.clone();
+        tauri::async_runtime::spawn(async move {
+            Self::renew(&app, &server).await;
+        });
+    }
+
+    async fn renew<R: Runtime>(app: &AppHandle<R>, server: &str) {
+        let settings = match SettingsService::load(app) {
+            Ok(settings) => settings,
+            Err(e) => {
+                eprintln!("[Keep Alive] {}", e);
+                return;
+            }
+        };
+
+        let now = Local::now();
+        if let Err(reason) = Self::allowed(app, &settings, server, now) {
+            app.state::<AppState>().keep_alive.next_check.lock().unwrap()
+                .insert(server.to_string(), Instant::now() + SKIP_FOR);
+            Self::record(app, server, RenewalOutcome::Skipped, Some(reason));
+            return;
+        }
+
+        match ServerService::request_access(app, server).await {
+            Ok(()) => Self::record(app, server, RenewalOutcome::Renewed, None),
+            Err(e) => Self::record(app, server, RenewalOutcome::Failed, Some(e)),
+        }
+    }
+
+    /// Working hours and the daily limit; `Err` carries the reason for the log.
+    fn allowed<R: Runtime>(app: &AppHandle<R>, settings: &Settings, server: &str, now: DateTime<Local>) -> Result<(), String> {
+        if settings.keep_alive_weekdays_only && matches!(now.weekday(), Weekday::Sat | Weekday::Sun) {
+            return Err("Outside working days".into());
+        }
+        if let Some(hours) = &settings.keep_alive_hours {
+            let (start, end) = parse_hours(hours)?;
+            if !within(now.time(), start, end) {
+                return Err(format!("Outside working hours ({})", hours));
+            }
+        }
+
+        let renewed_today = app.state::<AppState>().keep_alive.renewed_on(app, server, now.date_naive())?;
+        if renewed_today >= settings.keep_alive_daily_limit {
+            return Err(format!("Daily limit of {} renewals reached", settings.keep_alive_daily_limit));
+        }
+        Ok(())
+    }
+
+    fn record<R: Runtime>(app: &AppHandle<R>, server: &str, outcome: RenewalOutcome, reason: Option<String>) {
+        eprintln!("[Keep Alive] '{}': {:?} {}", server, outcome, reason.as_deref().unwrap_or_default());
+
+        let record = RenewalRecord {
+            time: Local::now(),
+            server: server.to_string(),
+            outcome,
+            reason,
+        };
+        if outcome == RenewalOutcome::Renewed {
+            app.state::<AppState>().keep_alive.count_renewal(server, record.time.date_naive());
+        }
+        if let Err(e) = LOG.append(app, &record) {
+            eprintln!("[Keep Alive] Failed to write log: {}", e);
+        }
+    }
+
+    /// The renewal log, oldest first.
+    pub fn log<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<RenewalRecord>, String> {
+        LOG.read(app)
+    }
+}
+
+fn prune<R: Runtime>(app: &AppHandle<R>) {
+    let cutoff = Local::now() - ChronoDuration::days(RETENTION_DAYS);
+    match LOG.retain(app, |record: &RenewalRecord| record.time >= cutoff) {
+        Ok(0) => {}
+        Ok(dropped) => eprintln!("[Keep Alive] Dropped {} records older than {} days", dropped, RETENTION_DAYS),
+        Err(e) => eprintln!("[Keep Alive] Failed to prune the keep-alive log: {}", e),
+    }
+}
+
+/// Parses working hours such as `08:00-18:00`. A start after the end spans midnight.
+pub fn parse_hours(hours: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let invalid = || format!("'{}' is not a valid time range, expected HH:MM-HH:MM", hours);
+    let (start, end) = hours.split_once('-').ok_or_else(invalid)?;
+    let start = NaiveTime::parse_from_str(start.trim(), "%H:%M").map_err(|_| invalid())?;
+    let end = NaiveTime::parse_from_str(end.trim(), "%H:%M").map_err(|_| invalid())?;
+    Ok((start, end))
+}
+
+fn within(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
+    if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    }
+}
@@ -3,39 +3,56 @@ use serde::{Deserialize, Serialize};
 use rand::{rng, Rng};
 use base64::{engine::general_purpose, Engine as _};
 
+use crate::core::profiles::DEFAULT_PROFILE;
+
 const SERVICE_SESSION: &str = "ServeMe_Session";
 const SERVICE_INTERNAL: &str = "ServeMe_Internal";
 const ACCOUNT_NAME: &str = "current_session";
+const ACCOUNT_PREFIX: &str = "session_";
 const KEY_NAME: &str = "master_key";
+const CLIENT_KEY_NAME: &str = "tls_client_key";
+const PROXY_CREDENTIALS_NAME: &str = "proxy_credentials";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LoginMethod {
+    #[default]
+    Password,
+    Sso,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Session {
     pub access_token: String,
     pub refresh_token: String,
+    #[serde(default)]
+    pub method: LoginMethod,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
 }
 
 pub struct KeychainService;
 
 impl KeychainService {
-    pub fn save_session(access: &str, refresh: &str) -> Result<(), String> {
-        let session = Session {
-            access_token: access.to_string(),
-            refresh_token: refresh.to_string(),
-        };
-        let json = serde_json::to_string(&session).map_err(|e| e.to_string())?;
+    pub fn save_session(profile: &str, session: &Session) -> Result<(), String> {
+        let json = serde_json::to_string(session).map_err(|e| e.to_string())?;
         
-        let entry = Entry::new(SERVICE_SESSION, ACCOUNT_NAME).map_err(|e| e.to_string())?;
+        let entry = Self::session_entry(profile)?;
         entry.set_password(&json).map_err(|e| e.to_string())
     }
 
-    pub fn get_session() -> Result<Session, String> {
-        let entry = Entry::new(SERVICE_SESSION, ACCOUNT_NAME).map_err(|e| e.to_string())?;
+    pub fn get_session(profile: &str) -> Result<Session, String> {
+        let entry = Self::session_entry(profile)?;
         let json = entry.get_password().map_err(|e| e.to_string())?;
         serde_json::from_str(&json).map_err(|e| e.to_string())
     }
 
-    pub fn purge_session() -> Result<(), String> {
-        let entry = Entry::new(SERVICE_SESSION, ACCOUNT_NAME).map_err(|e| e.to_string())?;
+    pub fn purge_session(profile: &str) -> Result<(), String> {
+        let entry = Self::session_entry(profile)?;
         match entry.delete_credential() {
             Ok(_) => Ok(()),
             Err(keyring::Error::NoEntry) => Ok(()),
@@ -61,4 +78,62 @@ impl KeychainService {
             Err(e) => Err(e.to_string()),
         }
     }
+
+    pub fn save_client_key(pem: &str) -> Result<(), String> {
+        let entry = Entry::new(SERVICE_INTERNAL, CLIENT_KEY_NAME).map_err(|e| e.to_string())?;
+        entry.set_password(pem).map_err(|e| e.to_string())
+    }
+
+    pub fn get_client_key() -> Result<Option<String>, String> {
+        let entry = Entry::new(SERVICE_INTERNAL, CLIENT_KEY_NAME).map_err(|e| e.to_string())?;
+        match entry.get_password() {
+            Ok(pem) => Ok(Some(pem)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn purge_client_key() -> Result<(), String> {
+        let entry = Entry::new(SERVICE_INTERNAL, CLIENT_KEY_NAME).map_err(|e| e.to_string())?;
+        match entry.delete_credential() {
+            Ok(_) => Ok(()),
+            Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn save_proxy_credentials(credentials: &ProxyCredentials) -> Result<(), String> {
+        let json = serde_json::to_string(credentials).map_err(|e| e.to_string())?;
+        let entry = Entry::new(SERVICE_INTERNAL, PROXY_CREDENTIALS_NAME).map_err(|e| e.to_string())?;
+        entry.set_password(&json).map_err(|e| e.to_string())
+    }
+
+    pub fn get_proxy_credentials() -> Result<Option<ProxyCredentials>, String> {
+        let entry = Entry::new(SERVICE_INTERNAL, PROXY_CREDENTIALS_NAME).map_err(|e| e.to_string())?;
+        match entry.get_password() {
+            Ok(json) => serde_json::from_str(&json).map(Some).map_err(|e| e.to_string()),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    pub fn purge_proxy_credentials() -> Result<(), String> {
+        let entry = Entry::new(SERVICE_INTERNAL, PROXY_CREDENTIALS_NAME).map_err(|e| e.to_string())?;
+        match entry.delete_credential() {
+            Ok(_) => Ok(()),
+            Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    // The default profile keeps the original account name so sessions
+    // created before profiles existed are picked up without a re-login.
+    fn session_entry(profile: &str) -> Result<Entry, String> {
+        let account = if profile == DEFAULT_PROFILE {
+            ACCOUNT_NAME.to_string()
+        } else {
+            format!("{}{}", ACCOUNT_PREFIX, profile)
+        };
+        Entry::new(SERVICE_SESSION, &account).map_err(|e| e.to_string())
+    }
 }
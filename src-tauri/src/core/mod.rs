@@ -1,3 +1,20 @@
 pub mod api;
+pub mod archive;
+pub mod backend;
+pub mod deep_link;
+pub mod history;
+pub mod journal;
+pub mod jwt;
+pub mod keep_alive;
 pub mod keychain;
-pub mod services;
\ No newline at end of file
+pub mod notifications;
+pub mod oidc;
+pub mod policy;
+pub mod profiles;
+pub mod proxy;
+pub mod scheduler;
+pub mod servers;
+pub mod services;
+pub mod settings;
+pub mod tls;
+pub mod watcher;
\ No newline at end of file
@@ -0,0 +1,161 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_notification::NotificationExt;
+use crate::core::servers::{ServerEntry, ServerStatus};
+use crate::core::settings::SettingsService;
+use crate::state::AppState;
+
+/// Action type of the expiry notifications. The dashboard registers it with an
+/// "Extend access" action. Desktop platforms have no notification actions, so
+/// there the tray menu lists servers with an outstanding warning instead.
+pub const EXPIRY_ACTION_TYPE: &str = "access-expiry";
+
+/// Emitted with the server id when an expiry warning is shown.
+pub const EXPIRY_WARNING_EVENT: &str = "expiry-warning";
+
+/// Lead times already announced for each server's current grant, so a warning
+/// is shown once and not on every poll, and the grants seen so far with the
+/// time they end (if the backend said), so their expiry is noticed even when
+/// no poll caught the last minute.
+#[derive(Default)]
+pub struct ExpiryNotices {
+    sent: Mutex<HashMap<String, Vec<u32>>>,
+    grants: Mutex<HashMap<String, Option<i64>>>,
+}
+
+impl ExpiryNotices {
+    /// Servers warned about whose grant has not been extended since.
+    pub fn expiring(&self) -> Vec<String> {
+        let sent = self.sent.lock().unwrap();
+        let mut servers: Vec<String> = sent.iter()
+            .filter(|(_, announced)| !announced.is_empty())
+            .map(|(server, _)| server.clone())
+            .collect();
+        servers.sort();
+        servers
+    }
+}
+
+pub struct NotificationService;
+
+impl NotificationService {
+    /// Called by the server registry after every poll, changed or not: with a
+    /// fixed expiration the entry stays the same while the deadline comes closer.
+    /// `signed_out` is set when the poll failed because the session is gone.
+    pub fn status_polled<R: Runtime>(app: &AppHandle<R>, next: &ServerEntry, signed_out: bool) {
+        let state = app.state::<AppState>();
+        let notices = &state.expiry_notices;
+        let now = Utc::now().timestamp();
+
+        match next.status {
+            ServerStatus::AccessGranted => {
+                let ends_at = next.expiration.or_else(|| next.minutes_left().map(|m| now + m * 60));
+                notices.grants.lock().unwrap().insert(next.id.clone(), ends_at);
+
+                let Some(minutes) = next.minutes_left() else {
+                    return;
+                };
+
+                let Ok(settings) = SettingsService::load(app) else {
+                    return;
+                };
+                let mut sent = notices.sent.lock().unwrap();
+                let announced = sent.entry(next.id.clone()).or_default();
+                // An extended grant is further out than the warnings given so far.
+                announced.retain(|lead| minutes <= i64::from(*lead));
+
+                let due: Vec<u32> = settings.expiry_warning_minutes.iter()
+                    .copied()
+                    .filter(|lead| minutes <= i64::from(*lead) && !announced.contains(lead))
+                    .collect();
+                if due.is_empty() {
+                    return;
+                }
+                // Several lead times can pass between two polls (e.g. after sleep); warn once.
+                announced.extend(due);
+                drop(sent);
+
+                if settings.expiry_notifications {
+                    let body = match minutes {
+                        m if m <= 1 => format!("Access to '{}' expires in less than a minute.", next.id),
+                        m => format!("Access to '{}' expires in {} minutes.", next.id, m),
+                    };
+                    Self::show(app, &next.id, "Access expiring", &body);
+                }
+                let _ = app.emit(EXPIRY_WARNING_EVENT, &next.id);
+            }
+            ServerStatus::Idle => {
+                notices.sent.lock().unwrap().remove(&next.id);
+                let grant = notices.grants.lock().unwrap().remove(&next.id);
+                if grant.is_some_and(|ends_at| has_expired(ends_at, signed_out, now)) {
+                    Self::expired(app, &next.id);
+                }
+            }
+            // Unknown until the backend is back, but a grant past its end is over either way.
+            ServerStatus::Offline => {
+                let mut grants = notices.grants.lock().unwrap();
+                if grants.get(&next.id).is_some_and(|ends_at| ends_at.is_some_and(|t| t <= now)) {
+                    grants.remove(&next.id);
+                    drop(grants);
+                    notices.sent.lock().unwrap().remove(&next.id);
+                    Self::expired(app, &next.id);
+                }
+            }
+        }
+    }
+
+    fn expired<R: Runtime>(app: &AppHandle<R>, server: &str) {
+        if SettingsService::load(app).is_ok_and(|s| s.expiry_notifications) {
+            let body = format!("Access to '{}' has expired.", server);
+            Self::show(app, server, "Access expired", &body);
+        }
+    }
+
+    /// A plain notification, for results of actions started outside the dashboard.
+    pub fn notify<R: Runtime>(app: &AppHandle<R>, title: &str, body: &str) {
+        if let Err(e) = app.notification().builder().title(title).body(body).show() {
+            eprintln!("[Notifications] Failed to show '{}': {}", title, e);
+        }
+    }
+
+    fn show<R: Runtime>(app: &AppHandle<R>, server: &str, title: &str, body: &str) {
+        let result = app.notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .action_type_id(EXPIRY_ACTION_TYPE)
+            .extra("server", server)
+            .show();
+
+        if let Err(e) = result {
+            eprintln!("[Notifications] Failed to show '{}' for '{}': {}", title, server, e);
+        }
+    }
+}
+
+/// Whether a grant that is no longer reported ran out. Logging out also turns
+/// servers idle, which is only an expiry if the grant had ended by then anyway.
+fn has_expired(ends_at: Option<i64>, signed_out: bool, now: i64) -> bool {
+    !signed_out || ends_at.is_some_and(|t| t <= now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grants_that_disappear_have_expired() {
+        // Whether the last poll saw one minute or thirty left does not matter.
+        assert!(has_expired(Some(2_000), false, 1_000));
+        assert!(has_expired(None, false, 1_000));
+    }
+
+    #[test]
+    fn signing_out_is_not_an_expiry() {
+        assert!(!has_expired(Some(2_000), true, 1_000));
+        assert!(!has_expired(None, true, 1_000));
+        assert!(has_expired(Some(900), true, 1_000));
+    }
+}
@@ -0,0 +1,601 @@
+use base64::{engine::general_purpose, Engine as _};
+use rand::{rng, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::Url;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const CALLBACK_PATH: &str = "/callback";
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+/// A connection that sends nothing must not hold up the browser's callback.
+const CALLBACK_READ_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_SCOPE: &str = "openid profile offline_access";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEFAULT_POLL_INTERVAL: u64 = 5;
+
+const CALLBACK_SUCCESS_HTML: &str = "<html><body><h3>Login complete</h3><p>You can close this window and return to ServeMe.</p></body></html>";
+const CALLBACK_FAILURE_HTML: &str = "<html><body><h3>Login failed</h3><p>Please return to ServeMe and try again.</p></body></html>";
+
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Discovery {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub device_authorization_endpoint: Option<String>,
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcTokens {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub id_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct DeviceAuthorization {
+    #[serde(skip_serializing)]
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+    #[serde(default)]
+    pub interval: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct OidcErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+enum TokenError {
+    Transport(String),
+    Oidc(OidcErrorResponse),
+}
+
+impl From<TokenError> for String {
+    fn from(err: TokenError) -> Self {
+        match err {
+            TokenError::Transport(msg) => msg,
+            TokenError::Oidc(err) => match err.error_description {
+                Some(desc) => format!("{}: {}", err.error, desc),
+                None => err.error,
+            },
+        }
+    }
+}
+
+/// Pending authorization code request: the PKCE verifier and the state value
+/// have to survive until the browser redirects back to the loopback listener.
+pub struct PkceRequest {
+    pub authorize_url: String,
+    redirect_uri: String,
+    verifier: String,
+    state: String,
+    listener: TcpListener,
+}
+
+pub struct OidcService;
+
+impl OidcService {
+    pub async fn discover(http: &reqwest::Client, config: &OidcConfig) -> Result<Discovery, String> {
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            config.issuer.trim_end_matches('/')
+        );
+
+        http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Discovery failed: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Discovery failed: {}", e))?
+            .json::<Discovery>()
+            .await
+            .map_err(|e| format!("Invalid discovery document: {}", e))
+    }
+
+    /// Binds a one-shot loopback listener on a random port and builds the
+    /// authorization URL that redirects back to it.
+    pub async fn begin_pkce(config: &OidcConfig, discovery: &Discovery) -> Result<PkceRequest, String> {
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(|e| format!("Failed to bind loopback listener: {}", e))?;
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+        let redirect_uri = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+
+        let verifier = random_urlsafe(32);
+        let state = random_urlsafe(16);
+        let challenge = general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+        let authorize_url = Url::parse_with_params(
+            &discovery.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", config.client_id.as_str()),
+                ("redirect_uri", redirect_uri.as_str()),
+                ("scope", config.scope.as_deref().unwrap_or(DEFAULT_SCOPE)),
+                ("state", state.as_str()),
+                ("code_challenge", challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|e| format!("Invalid authorization endpoint: {}", e))?;
+
+        Ok(PkceRequest {
+            authorize_url: authorize_url.to_string(),
+            redirect_uri,
+            verifier,
+            state,
+            listener,
+        })
+    }
+
+    /// Waits for the browser redirect, then exchanges the code for tokens.
+    pub async fn complete_pkce(
+        http: &reqwest::Client,
+        config: &OidcConfig,
+        discovery: &Discovery,
+        request: PkceRequest,
+    ) -> Result<OidcTokens, String> {
+        let code = tokio::time::timeout(
+            CALLBACK_TIMEOUT,
+            wait_for_callback(&request.listener, &request.state),
+        )
+        .await
+        .map_err(|_| "Timed out waiting for the browser login".to_string())??;
+
+        let form = [
+            ("grant_type", "authorization_code"),
+            ("code", code.as_str()),
+            ("redirect_uri", request.redirect_uri.as_str()),
+            ("client_id", config.client_id.as_str()),
+            ("code_verifier", request.verifier.as_str()),
+        ];
+        Ok(Self::token_request(http, &discovery.token_endpoint, &form).await?)
+    }
+
+    pub async fn refresh(http: &reqwest::Client, config: &OidcConfig, refresh_token: &str) -> Result<OidcTokens, String> {
+        let discovery = Self::discover(http, config).await?;
+        let form = [
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", config.client_id.as_str()),
+        ];
+        Ok(Self::token_request(http, &discovery.token_endpoint, &form).await?)
+    }
+
+    /// Starts an RFC 8628 device authorization request.
+    pub async fn begin_device(http: &reqwest::Client, config: &OidcConfig, discovery: &Discovery) -> Result<DeviceAuthorization, String> {
+        let endpoint = discovery.device_authorization_endpoint.as_deref()
+            .ok_or("Identity provider does not support device login")?;
+
+        let form = [
+            ("client_id", config.client_id.as_str()),
+            ("scope", config.scope.as_deref().unwrap_or(DEFAULT_SCOPE)),
+        ];
+        let response = http
+            .post(endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("Device authorization failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(read_oidc_error(response).await.into());
+        }
+
+        response
+            .json::<DeviceAuthorization>()
+            .await
+            .map_err(|e| format!("Invalid device authorization response: {}", e))
+    }
+
+    /// Polls the token endpoint until the user approves the device code,
+    /// honouring `authorization_pending` and `slow_down` as per RFC 8628.
+    pub async fn poll_device(
+        http: &reqwest::Client,
+        config: &OidcConfig,
+        discovery: &Discovery,
+        authorization: &DeviceAuthorization,
+        cancelled: &AtomicBool,
+    ) -> Result<OidcTokens, String> {
+        let deadline = Instant::now() + Duration::from_secs(authorization.expires_in);
+        let mut interval = authorization.interval.unwrap_or(DEFAULT_POLL_INTERVAL);
+
+        let form = [
+            ("grant_type", DEVICE_CODE_GRANT),
+            ("device_code", authorization.device_code.as_str()),
+            ("client_id", config.client_id.as_str()),
+        ];
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+
+            if cancelled.load(Ordering::Relaxed) {
+                return Err("Device login was cancelled".into());
+            }
+            if Instant::now() >= deadline {
+                return Err("Device code expired before it was approved".into());
+            }
+
+            match Self::token_request(http, &discovery.token_endpoint, &form).await {
+                Ok(tokens) => return Ok(tokens),
+                Err(TokenError::Oidc(err)) if err.error == "authorization_pending" => {}
+                Err(TokenError::Oidc(err)) if err.error == "slow_down" => interval += 5,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    async fn token_request(
+        http: &reqwest::Client,
+        token_endpoint: &str,
+        form: &[(&str, &str)],
+    ) -> Result<OidcTokens, TokenError> {
+        let response = http
+            .post(token_endpoint)
+            .form(form)
+            .send()
+            .await
+            .map_err(|e| TokenError::Transport(format!("Token request failed: {}", e)))?;
+
+        if response.status().is_success() {
+            return response
+                .json::<OidcTokens>()
+                .await
+                .map_err(|e| TokenError::Transport(format!("Invalid token response: {}", e)));
+        }
+
+        Err(read_oidc_error(response).await)
+    }
+}
+
+/// Reads an RFC 6749 error body, keeping the error code so callers can match on it.
+async fn read_oidc_error(response: reqwest::Response) -> TokenError {
+    let status = response.status();
+    match response.json::<OidcErrorResponse>().await {
+        Ok(err) => TokenError::Oidc(err),
+        Err(_) => TokenError::Transport(format!("Identity provider returned {}", status)),
+    }
+}
+
+async fn wait_for_callback(listener: &TcpListener, expected_state: &str) -> Result<String, String> {
+    loop {
+        let (mut socket, _) = listener.accept().await.map_err(|e| e.to_string())?;
+
+        let target = match tokio::time::timeout(CALLBACK_READ_TIMEOUT, read_request_target(&mut socket)).await {
+            Ok(Some(t)) => t,
+            Ok(None) | Err(_) => continue,
+        };
+        let url = match Url::parse(&format!("http://127.0.0.1{}", target)) {
+            Ok(u) if u.path() == CALLBACK_PATH => u,
+            // Browsers also ask for favicons and the like; ignore those.
+            _ => {
+                respond(&mut socket, 404, "").await;
+                continue;
+            }
+        };
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.into_owned())
+        };
+
+        // Anything on the machine can reach the listener; a callback without our
+        // state did not come from this login, so it must not end it either.
+        if param("state").as_deref() != Some(expected_state) {
+            respond(&mut socket, 400, CALLBACK_FAILURE_HTML).await;
+            continue;
+        }
+
+        if let Some(error) = param("error") {
+            respond(&mut socket, 400, CALLBACK_FAILURE_HTML).await;
+            return Err(match param("error_description") {
+                Some(desc) => format!("{}: {}", error, desc),
+                None => error,
+            });
+        }
+
+        return match param("code") {
+            Some(code) => {
+                respond(&mut socket, 200, CALLBACK_SUCCESS_HTML).await;
+                Ok(code)
+            }
+            None => {
+                respond(&mut socket, 400, CALLBACK_FAILURE_HTML).await;
+                Err("Login callback did not contain a code".into())
+            }
+        };
+    }
+}
+
+async fn read_request_target(socket: &mut TcpStream) -> Option<String> {
+    let mut buffer = [0; 4096];
+    let n = match socket.read(&mut buffer).await {
+        Ok(n) if n > 0 => n,
+        _ => return None,
+    };
+
+    let request = String::from_utf8_lossy(&buffer[..n]);
+    request
+        .lines()
+        .next()?
+        .split_whitespace()
+        .nth(1)
+        .map(String::from)
+}
+
+async fn respond(socket: &mut TcpStream, status: u16, body: &str) {
+    let status_text = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        _ => "Not Found",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, status_text, body.len(), body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+    let _ = socket.flush().await;
+}
+
+fn random_urlsafe(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rng().fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query_param(url: &str, name: &str) -> String {
+        Url::parse(url).unwrap()
+            .query_pairs()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+            .unwrap()
+    }
+
+    /// Reads one form POST and returns its fields.
+    async fn read_form(socket: &mut TcpStream) -> Vec<(String, String)> {
+        let mut request = Vec::new();
+        let mut buffer = [0; 4096];
+        let body_start = loop {
+            let n = socket.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..n]);
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+        let length: usize = headers.lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .map(|value| value.trim().parse().unwrap())
+            .unwrap_or_default();
+        while request.len() < body_start + length {
+            let n = socket.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..n]);
+        }
+
+        Url::parse(&format!("http://stub/?{}", String::from_utf8_lossy(&request[body_start..]))).unwrap()
+            .query_pairs()
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect()
+    }
+
+    async fn reply_json(socket: &mut TcpStream, status: u16, body: &str) {
+        let response = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, if status == 200 { "OK" } else { "Bad Request" }, body.len(), body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    }
+
+    /// A token endpoint that answers one request and hands back the form it got.
+    async fn stub_token_endpoint(listener: TcpListener) -> Vec<(String, String)> {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let form = read_form(&mut socket).await;
+        reply_json(&mut socket, 200, TOKENS).await;
+        form
+    }
+
+    /// A token endpoint for device polls that gives the responses in order and
+    /// returns when each poll arrived, on the test's (paused) clock.
+    async fn stub_device_endpoint(listener: TcpListener, responses: &[(u16, &str)]) -> Vec<Duration> {
+        let start = tokio::time::Instant::now();
+        let mut arrivals = Vec::new();
+        for (status, body) in responses {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let form = read_form(&mut socket).await;
+            assert!(form.contains(&("grant_type".to_string(), DEVICE_CODE_GRANT.to_string())));
+            assert!(form.contains(&("device_code".to_string(), "device-code".to_string())));
+            arrivals.push(start.elapsed());
+            reply_json(&mut socket, *status, body).await;
+        }
+        arrivals
+    }
+
+    const TOKENS: &str = r#"{"access_token":"access","refresh_token":"refresh"}"#;
+    const PENDING: (u16, &str) = (400, r#"{"error":"authorization_pending"}"#);
+    const SLOW_DOWN: (u16, &str) = (400, r#"{"error":"slow_down"}"#);
+
+    async fn idp() -> (TcpListener, OidcConfig, Discovery) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let config = OidcConfig { issuer: url.clone(), client_id: "serveme".into(), scope: None };
+        let discovery = Discovery {
+            authorization_endpoint: format!("{}/authorize", url),
+            token_endpoint: format!("{}/token", url),
+            device_authorization_endpoint: Some(format!("{}/device", url)),
+            jwks_uri: None,
+        };
+        (listener, config, discovery)
+    }
+
+    fn device_authorization(interval: u64) -> DeviceAuthorization {
+        DeviceAuthorization {
+            device_code: "device-code".into(),
+            user_code: "ABCD-EFGH".into(),
+            verification_uri: "https://idp.example/device".into(),
+            verification_uri_complete: None,
+            expires_in: 600,
+            interval: Some(interval),
+        }
+    }
+
+    fn http() -> reqwest::Client {
+        reqwest::Client::builder().no_proxy().build().unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn device_polling_waits_while_authorization_is_pending() {
+        let (listener, config, discovery) = idp().await;
+        let cancelled = AtomicBool::new(false);
+        let (tokens, arrivals) = tokio::join!(
+            OidcService::poll_device(&http(), &config, &discovery, &device_authorization(2), &cancelled),
+            stub_device_endpoint(listener, &[PENDING, PENDING, (200, TOKENS)]),
+        );
+
+        assert_eq!(tokens.unwrap().access_token, "access");
+        assert!(arrivals[0] >= Duration::from_secs(2));
+        assert!(arrivals[2] - arrivals[1] >= Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn device_polling_backs_off_on_slow_down() {
+        let (listener, config, discovery) = idp().await;
+        let cancelled = AtomicBool::new(false);
+        let (tokens, arrivals) = tokio::join!(
+            OidcService::poll_device(&http(), &config, &discovery, &device_authorization(1), &cancelled),
+            stub_device_endpoint(listener, &[SLOW_DOWN, SLOW_DOWN, (200, TOKENS)]),
+        );
+
+        assert!(tokens.is_ok());
+        // Each slow_down adds five seconds to the interval for good.
+        assert!(arrivals[1] - arrivals[0] >= Duration::from_secs(6));
+        assert!(arrivals[2] - arrivals[1] >= Duration::from_secs(11));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn device_polling_stops_on_final_errors() {
+        for (body, expected) in [
+            (r#"{"error":"expired_token"}"#, "expired_token"),
+            (r#"{"error":"access_denied","error_description":"User declined"}"#, "access_denied: User declined"),
+        ] {
+            let (listener, config, discovery) = idp().await;
+            let cancelled = AtomicBool::new(false);
+            let (result, arrivals) = tokio::join!(
+                OidcService::poll_device(&http(), &config, &discovery, &device_authorization(1), &cancelled),
+                stub_device_endpoint(listener, &[PENDING, (400, body)]),
+            );
+            assert_eq!(result.err().as_deref(), Some(expected));
+            assert_eq!(arrivals.len(), 2);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn device_polling_stops_when_cancelled() {
+        let (listener, config, discovery) = idp().await;
+        let cancelled = AtomicBool::new(false);
+        let user = async {
+            stub_device_endpoint(listener, &[PENDING]).await;
+            // The listener is gone now; another poll would fail with a transport error.
+            cancelled.store(true, Ordering::Relaxed);
+        };
+        let (result, ()) = tokio::join!(
+            OidcService::poll_device(&http(), &config, &discovery, &device_authorization(1), &cancelled),
+            user,
+        );
+        assert_eq!(result.err().as_deref(), Some("Device login was cancelled"));
+    }
+
+    #[tokio::test]
+    async fn authorization_code_flow_ignores_foreign_callbacks() {
+        let (idp, config, discovery) = idp().await;
+        let http = http();
+
+        let request = OidcService::begin_pkce(&config, &discovery).await.unwrap();
+        let authorize_url = request.authorize_url.clone();
+        let redirect_uri = query_param(&authorize_url, "redirect_uri");
+        let state = query_param(&authorize_url, "state");
+        let challenge = query_param(&authorize_url, "code_challenge");
+        assert_eq!(query_param(&authorize_url, "code_challenge_method"), "S256");
+
+        let browser = async {
+            let forged = http.get(format!("{}?state=forged&error=access_denied", redirect_uri)).send().await.unwrap();
+            assert_eq!(forged.status(), 400);
+            let missing = http.get(format!("{}?code=stolen", redirect_uri)).send().await.unwrap();
+            assert_eq!(missing.status(), 400);
+
+            let callback = Url::parse_with_params(&redirect_uri, &[("state", state.as_str()), ("code", "the-code")]).unwrap();
+            let done = http.get(callback.as_str()).send().await.unwrap();
+            assert_eq!(done.status(), 200);
+        };
+
+        let (tokens, form, ()) = tokio::join!(
+            OidcService::complete_pkce(&http, &config, &discovery, request),
+            stub_token_endpoint(idp),
+            browser,
+        );
+
+        let tokens = tokens.unwrap();
+        assert_eq!(tokens.access_token, "access");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+
+        let field = |name: &str| form.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str());
+        assert_eq!(field("grant_type"), Some("authorization_code"));
+        assert_eq!(field("code"), Some("the-code"));
+        assert_eq!(field("redirect_uri"), Some(redirect_uri.as_str()));
+        let verifier = field("code_verifier").unwrap();
+        assert_eq!(general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())), challenge);
+    }
+
+    #[tokio::test]
+    async fn idle_connections_do_not_block_the_callback() {
+        let (idp, config, discovery) = idp().await;
+        let http = http();
+
+        let request = OidcService::begin_pkce(&config, &discovery).await.unwrap();
+        let redirect_uri = query_param(&request.authorize_url, "redirect_uri");
+        let state = query_param(&request.authorize_url, "state");
+
+        let browser = async {
+            // Connects first and never sends a request.
+            let address = Url::parse(&redirect_uri).unwrap().socket_addrs(|| None).unwrap()[0];
+            let _idle = TcpStream::connect(address).await.unwrap();
+
+            let callback = Url::parse_with_params(&redirect_uri, &[("state", state.as_str()), ("code", "the-code")]).unwrap();
+            let done = http.get(callback.as_str()).send().await.unwrap();
+            assert_eq!(done.status(), 200);
+        };
+
+        let (tokens, _, ()) = tokio::join!(
+            OidcService::complete_pkce(&http, &config, &discovery, request),
+            stub_token_endpoint(idp),
+            browser,
+        );
+        assert_eq!(tokens.unwrap().access_token, "access");
+    }
+}
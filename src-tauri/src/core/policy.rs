@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use crate::core::settings::Settings;
+
+#[cfg(target_os = "linux")]
+const POLICY_FILE: &str = "/etc/serve-me/policy.json";
+#[cfg(target_os = "macos")]
+const POLICY_FILE: &str = "/Library/Application Support/ServeMe/policy.json";
+#[cfg(target_os = "windows")]
+const POLICY_FILE: &str = "C:\\ProgramData\\ServeMe\\policy.json";
+
+/// System-wide policy written by an administrator, e.g.
+///
+/// ```json
+/// {
+///   "settings": { "server_url": "https://access.corp.example", "extensions_enabled": true },
+///   "autostart": true,
+///   "allowed_extensions": ["db-tunnel"]
+/// }
+/// ```
+///
+/// Every key under `settings` is forced over the user's `settings.json` and
+/// becomes read-only. `autostart` pins the login item, and `allowed_extensions`
+/// lists the extension names (without version) that may be uploaded and run.
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Policy {
+    pub settings: Map<String, Value>,
+    pub autostart: Option<bool>,
+    pub allowed_extensions: Option<Vec<String>>,
+}
+
+/// What the UI needs to know to disable locked controls.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySummary {
+    pub locked_keys: Vec<String>,
+    pub autostart_locked: bool,
+    pub allowed_extensions: Option<Vec<String>>,
+}
+
+impl Policy {
+    /// Reads the policy file once at startup. A missing file means no policy;
+    /// an unreadable one is logged and ignored rather than blocking the app.
+    pub fn load() -> Self {
+        let path = Path::new(POLICY_FILE);
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                eprintln!("[Policy] Failed to read {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+
+        let mut policy: Self = match serde_json::from_str(&content) {
+            Ok(policy) => policy,
+            Err(e) => {
+                eprintln!("[Policy] Ignoring invalid {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+
+        // A bad value would make every settings read fail, so drop it up front.
+        policy.settings.retain(|key, value| {
+            let valid = key != "schema_version" && accepts(key, value);
+            if !valid {
+                eprintln!("[Policy] Ignoring invalid value for '{}'", key);
+            }
+            valid
+        });
+        policy
+    }
+
+    pub fn is_locked(&self, key: &str) -> bool {
+        self.settings.contains_key(key)
+    }
+
+    /// Overlays the policy values on the user's settings.
+    pub fn apply(&self, json: &mut Map<String, Value>) {
+        for (key, value) in &self.settings {
+            json.insert(key.clone(), value.clone());
+        }
+    }
+
+    /// Fails if `json` changed a locked key; otherwise puts the user's own
+    /// values back, so the file keeps them if the policy is later lifted.
+    pub fn restore(&self, json: &mut Map<String, Value>, user: &Map<String, Value>) -> Result<(), String> {
+        for (key, value) in &self.settings {
+            if json.get(key) != Some(value) {
+                return Err(format!("'{}' is managed by your administrator", key));
+            }
+            match user.get(key) {
+                Some(own) => json.insert(key.clone(), own.clone()),
+                None => json.remove(key),
+            };
+        }
+        Ok(())
+    }
+
+    pub fn extensions_disabled(&self) -> bool {
+        self.settings.get("extensions_enabled") == Some(&Value::Bool(false))
+    }
+
+    /// Checks an extension file or bundle name against the policy.
+    pub fn check_extension(&self, base_name: &str) -> Result<(), String> {
+        if self.extensions_disabled() {
+            return Err("Extensions are disabled by your administrator".into());
+        }
+        if let Some(allowed) = &self.allowed_extensions {
+            if !allowed.iter().any(|name| name == base_name) {
+                return Err(format!("Extension '{}' is not allowed by your administrator", base_name));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn summary(&self) -> PolicySummary {
+        PolicySummary {
+            locked_keys: self.settings.keys().cloned().collect(),
+            autostart_locked: self.autostart.is_some(),
+            allowed_extensions: self.allowed_extensions.clone(),
+        }
+    }
+}
+
+fn accepts(key: &str, value: &Value) -> bool {
+    let mut json = match serde_json::to_value(Settings::default()) {
+        Ok(Value::Object(map)) => map,
+        _ => return false,
+    };
+    json.insert(key.to_string(), value.clone());
+    serde_json::from_value::<Settings>(Value::Object(json)).is_ok_and(|s| s.validate().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(value: Value) -> Policy {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn locked_keys_are_forced_and_restored() {
+        let policy = policy(json!({ "settings": { "server_url": "https://corp.example" } }));
+        let user = json!({ "server_url": "https://mine.example", "username": "ada" }).as_object().unwrap().clone();
+
+        let mut json = user.clone();
+        policy.apply(&mut json);
+        assert_eq!(json["server_url"], "https://corp.example");
+        assert!(policy.is_locked("server_url"));
+        assert!(!policy.is_locked("username"));
+
+        json.insert("username".into(), json!("grace"));
+        policy.restore(&mut json, &user).unwrap();
+        assert_eq!(json["server_url"], "https://mine.example");
+        assert_eq!(json["username"], "grace");
+    }
+
+    #[test]
+    fn changing_a_locked_key_is_rejected() {
+        let policy = policy(json!({ "settings": { "server_url": "https://corp.example" } }));
+        let user = Map::new();
+
+        let mut json = user.clone();
+        policy.apply(&mut json);
+        json.insert("server_url".into(), json!("https://elsewhere.example"));
+        assert!(policy.restore(&mut json, &user).is_err());
+    }
+
+    #[test]
+    fn keys_the_user_never_set_are_dropped_on_restore() {
+        let policy = policy(json!({ "settings": { "extensions_enabled": false } }));
+        let mut json = Map::new();
+        policy.apply(&mut json);
+        policy.restore(&mut json, &Map::new()).unwrap();
+        assert!(json.is_empty());
+    }
+
+    #[test]
+    fn extensions_are_checked_against_the_allow_list() {
+        let policy = policy(json!({ "allowed_extensions": ["db-tunnel"] }));
+        assert!(policy.check_extension("db-tunnel").is_ok());
+        assert!(policy.check_extension("other").is_err());
+
+        let open = Policy::default();
+        assert!(open.check_extension("anything").is_ok());
+    }
+
+    #[test]
+    fn disabled_extensions_win_over_the_allow_list() {
+        let policy = policy(json!({
+            "settings": { "extensions_enabled": false },
+            "allowed_extensions": ["db-tunnel"]
+        }));
+        assert!(policy.extensions_disabled());
+        assert!(policy.check_extension("db-tunnel").is_err());
+    }
+
+    #[test]
+    fn only_valid_setting_values_are_accepted() {
+        assert!(accepts("extensions_enabled", &json!(true)));
+        assert!(!accepts("extensions_enabled", &json!("yes")));
+        assert!(accepts("server_url", &json!("https://corp.example")));
+        assert!(!accepts("server_url", &json!("ftp://corp.example")));
+        assert!(!accepts("refresh_interval_secs", &json!(0)));
+    }
+
+    #[test]
+    fn summary_lists_locked_keys() {
+        let policy = policy(json!({ "settings": { "server_url": "https://corp.example" }, "autostart": true }));
+        let summary = policy.summary();
+        assert_eq!(summary.locked_keys, vec!["server_url".to_string()]);
+        assert!(summary.autostart_locked);
+        assert!(summary.allowed_extensions.is_none());
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+use crate::core::keychain::KeychainService;
+use crate::core::settings::{validate_url, SettingsService};
+use crate::state::AppState;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub name: String,
+    pub server_url: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub oidc_issuer: Option<String>,
+    #[serde(default)]
+    pub oidc_client_id: Option<String>,
+}
+
+/// Named backend profiles stored in `settings.json`.
+///
+/// The top-level `server_url`, `username` and `oidc_*` keys always describe the active
+/// profile, so the UI and the refresh loop keep working unchanged. The
+/// `profiles` list holds the values of every profile and is brought up to date
+/// from the top-level keys whenever the active profile is read or switched.
+pub struct ProfileService;
+
+impl ProfileService {
+    pub fn list<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<Profile>, String> {
+        let settings = SettingsService::load(app)?;
+        let mut profiles = settings.profiles;
+
+        let current = Profile {
+            name: settings.active_profile.clone(),
+            server_url: settings.server_url,
+            username: settings.username,
+            oidc_issuer: settings.oidc_issuer,
+            oidc_client_id: settings.oidc_client_id,
+        };
+
+        match profiles.iter_mut().find(|p| p.name == settings.active_profile) {
+            Some(p) => *p = current,
+            None => profiles.insert(0, current),
+        }
+
+        Ok(profiles)
+    }
+
+    pub fn active<R: Runtime>(app: &AppHandle<R>) -> Result<Profile, String> {
+        let active = Self::active_name(app);
+        Self::list(app)?
+            .into_iter()
+            .find(|p| p.name == active)
+            .ok_or_else(|| format!("Profile '{}' not found", active))
+    }
+
+    pub fn active_name<R: Runtime>(app: &AppHandle<R>) -> String {
+        SettingsService::load(app)
+            .map(|s| s.active_profile)
+            .unwrap_or_else(|_| DEFAULT_PROFILE.to_string())
+    }
+
+    pub fn create<R: Runtime>(app: &AppHandle<R>, profile: Profile) -> Result<(), String> {
+        validate_name(&profile.name)?;
+        validate_url("server_url", &profile.server_url)?;
+        if let Some(issuer) = profile.oidc_issuer.as_deref().filter(|s| !s.is_empty()) {
+            validate_url("oidc_issuer", issuer)?;
+        }
+
+        let mut profiles = Self::list(app)?;
+        if profiles.iter().any(|p| p.name == profile.name) {
+            return Err(format!("Profile '{}' already exists", profile.name));
+        }
+
+        profiles.push(Profile {
+            server_url: profile.server_url.trim_end_matches('/').to_string(),
+            ..profile
+        });
+        Self::persist(app, &profiles, None)
+    }
+
+    pub fn switch<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<Profile, String> {
+        let profiles = Self::list(app)?;
+        let target = profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .ok_or_else(|| format!("Profile '{}' not found", name))?;
+
+        Self::persist(app, &profiles, Some(&target))?;
+        Ok(target)
+    }
+
+    pub fn delete<R: Runtime>(app: &AppHandle<R>, name: &str) -> Result<(), String> {
+        if name == DEFAULT_PROFILE {
+            return Err("The default profile cannot be deleted".into());
+        }
+        if name == Self::active_name(app) {
+            return Err("Switch to another profile before deleting this one".into());
+        }
+
+        let mut profiles = Self::list(app)?;
+        let before = profiles.len();
+        profiles.retain(|p| p.name != name);
+        if profiles.len() == before {
+            return Err(format!("Profile '{}' not found", name));
+        }
+
+        KeychainService::purge_session(name)?;
+        Self::persist(app, &profiles, None)
+    }
+
+    fn persist<R: Runtime>(
+        app: &AppHandle<R>,
+        profiles: &[Profile],
+        activate: Option<&Profile>,
+    ) -> Result<(), String> {
+        let state = app.state::<AppState>();
+        // Keys the admin locked keep the policy value whichever profile is active.
+        let unlocked = |key: &str| !state.policy.is_locked(key);
+
+        SettingsService::modify(app, |settings| {
+            settings.profiles = profiles.to_vec();
+
+            if let Some(profile) = activate {
+                settings.active_profile = profile.name.clone();
+                if unlocked("server_url") {
+                    settings.server_url = profile.server_url.clone();
+                }
+                if unlocked("username") {
+                    settings.username = profile.username.clone();
+                }
+                if unlocked("oidc_issuer") {
+                    settings.oidc_issuer = profile.oidc_issuer.clone();
+                }
+                if unlocked("oidc_client_id") {
+                    settings.oidc_client_id = profile.oidc_client_id.clone();
+                }
+            }
+            Ok(())
+        })
+        .map(|_| ())
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valid {
+        Ok(())
+    } else {
+        Err("Profile names may only contain letters, digits, '-' and '_'".into())
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Runtime, Url};
+use crate::core::keychain::{KeychainService, ProxyCredentials};
+use crate::core::settings::SettingsService;
+
+const SUPPORTED_SCHEMES: [&str; 4] = ["http", "https", "socks5", "socks5h"];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyMode {
+    /// Whatever reqwest finds in the environment (`HTTPS_PROXY` and friends).
+    #[default]
+    System,
+    /// Direct connections, ignoring the environment.
+    None,
+    Manual,
+}
+
+/// Proxy options for backend traffic. Only the username is shown to the UI;
+/// both the username and the password are stored in the keychain.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub mode: ProxyMode,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Comma separated hosts, domains (`.corp.example`) and CIDR ranges.
+    #[serde(default)]
+    pub no_proxy: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub has_password: bool,
+}
+
+impl ProxySettings {
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let credentials = KeychainService::get_proxy_credentials()?;
+        Ok(Self {
+            username: credentials.as_ref().map(|c| c.username.clone()),
+            has_password: credentials.is_some_and(|c| !c.password.is_empty()),
+            ..SettingsService::load(app)?.proxy()
+        })
+    }
+
+    pub fn save<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), String> {
+        SettingsService::modify(app, |settings| {
+            settings.proxy_mode = self.mode;
+            settings.proxy_url = self.url.clone().filter(|s| !s.is_empty());
+            settings.proxy_no_proxy = self.no_proxy.clone().filter(|s| !s.is_empty());
+            Ok(())
+        })
+        .map(|_| ())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.mode != ProxyMode::Manual {
+            return Ok(());
+        }
+
+        let url = self.url.as_deref().ok_or("A proxy URL is required")?;
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+        if !SUPPORTED_SCHEMES.contains(&parsed.scheme()) {
+            return Err("Proxy URL must start with http://, https://, socks5:// or socks5h://".into());
+        }
+        if parsed.host_str().is_none() {
+            return Err("Proxy URL must include a host".into());
+        }
+        Ok(())
+    }
+}
+
+/// Applies the proxy settings to a client builder.
+pub fn apply(
+    builder: reqwest::ClientBuilder,
+    settings: &ProxySettings,
+    credentials: Option<&ProxyCredentials>,
+) -> Result<reqwest::ClientBuilder, String> {
+    match settings.mode {
+        ProxyMode::System => Ok(builder),
+        ProxyMode::None => Ok(builder.no_proxy()),
+        ProxyMode::Manual => {
+            settings.validate()?;
+            let url = settings.url.as_deref().unwrap_or_default();
+
+            // Sets userinfo on the proxy URL, which covers both HTTP Basic and SOCKS5 auth.
+            let mut proxy = reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            if let Some(credentials) = credentials {
+                proxy = proxy.basic_auth(&credentials.username, &credentials.password);
+            }
+            let no_proxy = settings.no_proxy.as_deref().and_then(reqwest::NoProxy::from_string);
+
+            Ok(builder.proxy(proxy.no_proxy(no_proxy)))
+        }
+    }
+}
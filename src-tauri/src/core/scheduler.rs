@@ -0,0 +1,471 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::Notify;
+use crate::core::backend::validate_server_id;
+use crate::core::journal::Journal;
+use crate::core::servers::{AccessResult, ServerService};
+use crate::core::settings::{Settings, SettingsService};
+use crate::state::AppState;
+
+const LOG: Journal = Journal::new("schedule_log.jsonl");
+
+/// Records older than this are dropped when the scheduler starts. The log is
+/// for checking recent runs, not an audit trail; that is the access history.
+const RETENTION_DAYS: i64 = 90;
+
+/// Longest sleep between checks. Timers do not run while the machine is
+/// asleep, so this bounds how long after waking a missed run is noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+/// A run missed by at most this much (sleep, a busy machine) still fires once
+/// it is noticed; anything older is logged as missed instead.
+const CATCH_UP_WINDOW_MINUTES: i64 = 15;
+const PREVIEW_RUNS: usize = 5;
+/// Missed runs are counted up to this many; the log only needs a rough idea.
+const MAX_COUNTED_RUNS: usize = 1000;
+
+const DAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// What a schedule requests access to.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleTarget {
+    Server(String),
+    Group(String),
+}
+
+/// When a schedule fires: a cron expression, or once at a fixed time (a
+/// maintenance window).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleRule {
+    /// Five fields (`30 8 * * Mon-Fri`), or six/seven with seconds and year.
+    /// Day-of-week numbers are read as in crontab: 0 or 7 is Sunday, 1 Monday.
+    Cron(String),
+    Once(DateTime<Local>),
+}
+
+/// An entry of `access_schedules` in settings.json.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AccessSchedule {
+    pub id: String,
+    pub target: ScheduleTarget,
+    pub rule: ScheduleRule,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// A schedule as shown in the UI, with its next runs.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleEntry {
+    #[serde(flatten)]
+    pub schedule: AccessSchedule,
+    pub next_runs: Vec<DateTime<Local>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobOutcome {
+    Succeeded,
+    /// Some servers of a group failed.
+    Partial,
+    Failed,
+    /// The run was noticed too late (see `CATCH_UP_WINDOW_MINUTES`).
+    Missed,
+}
+
+/// One line of `schedule_log.jsonl`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JobRecord {
+    pub time: DateTime<Local>,
+    pub schedule: String,
+    pub target: ScheduleTarget,
+    pub scheduled_for: DateTime<Local>,
+    pub outcome: JobOutcome,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub results: Vec<AccessResult>,
+    pub reason: Option<String>,
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    wakeup: Notify,
+}
+
+impl Scheduler {
+    /// Recomputes the next run, e.g. after the schedules changed.
+    pub fn wake(&self) {
+        self.wakeup.notify_one();
+    }
+}
+
+impl AccessSchedule {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.target {
+            ScheduleTarget::Server(server) => {
+                validate_server_id(server).map_err(|e| format!("Schedule '{}': {}", self.id, e))?
+            }
+            ScheduleTarget::Group(group) if group.trim().is_empty() => {
+                return Err(format!("Schedule '{}' has no target", self.id));
+            }
+            ScheduleTarget::Group(_) => {}
+        }
+        self.rule.validate()
+    }
+}
+
+impl ScheduleRule {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ScheduleRule::Cron(expr) => parse_cron(expr).map(|_| ()),
+            ScheduleRule::Once(_) => Ok(()),
+        }
+    }
+
+    /// Up to `count` runs after `after`.
+    pub fn upcoming(&self, after: DateTime<Local>, count: usize) -> Vec<DateTime<Local>> {
+        match self {
+            ScheduleRule::Cron(expr) => match parse_cron(expr) {
+                Ok(schedule) => schedule.after(&after).take(count).collect(),
+                Err(_) => Vec::new(),
+            },
+            ScheduleRule::Once(at) => Some(*at).filter(|at| *at > after).into_iter().take(count).collect(),
+        }
+    }
+
+    /// The latest run in `(from, to]` and how many runs fell into that range,
+    /// counted up to `MAX_COUNTED_RUNS`.
+    fn due(&self, from: DateTime<Local>, to: DateTime<Local>) -> Option<(DateTime<Local>, usize)> {
+        match self {
+            ScheduleRule::Cron(expr) => {
+                let schedule = parse_cron(expr).ok()?;
+                // Searching backwards finds the latest run without walking a long sleep.
+                // The nanosecond makes `to` itself count when it falls on a whole second.
+                let latest = schedule.after(&(to + ChronoDuration::nanoseconds(1)))
+                    .next_back()
+                    .filter(|run| *run > from)?;
+                let count = schedule.after(&from)
+                    .take_while(|run| *run <= to)
+                    .take(MAX_COUNTED_RUNS)
+                    .count();
+                Some((latest, count))
+            }
+            ScheduleRule::Once(at) => Some(*at).filter(|at| from < *at && *at <= to).map(|at| (at, 1)),
+        }
+    }
+}
+
+/// The cron crate wants a seconds field; the familiar five-field form runs at second 0.
+/// It also counts weekdays from 1 = Sunday, so numeric days are turned into names.
+fn parse_cron(expr: &str) -> Result<cron::Schedule, String> {
+    let expr = expr.trim();
+    let invalid = |e: String| format!("'{}' is not a valid cron expression: {}", expr, e);
+
+    let mut fields: Vec<String> = expr.split_whitespace().map(String::from).collect();
+    if fields.len() == 5 {
+        fields.insert(0, "0".into());
+    }
+    if let Some(days) = fields.get_mut(5) {
+        *days = unix_days_of_week(days).map_err(invalid)?;
+    }
+    cron::Schedule::from_str(&fields.join(" ")).map_err(|e| invalid(e.to_string()))
+}
+
+/// Rewrites crontab day-of-week numbers (0-7, Sunday twice) as day names, which
+/// mean the same to the cron crate. Names, `*` and `?` are left alone.
+fn unix_days_of_week(field: &str) -> Result<String, String> {
+    let day = |value: &str| match value.parse::<usize>() {
+        Ok(day) if day <= 7 => Ok(day),
+        _ => Err(format!("'{}' is not a day of the week (0-7)", value)),
+    };
+
+    let items = field.split(',').map(|item| {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(step)),
+            None => (item, None),
+        };
+        if matches!(item, "*" | "?") || range.chars().any(|c| c.is_ascii_alphabetic()) {
+            return Ok(item.to_string());
+        }
+
+        let (start, end) = match (range, range.split_once('-')) {
+            ("*", _) => (0, 6),
+            (_, Some((start, end))) => (day(start)?, day(end)?),
+            // `1/2`: from that day to the end of the week.
+            (start, None) => (day(start)?, if step.is_some() { 6 } else { day(start)? }),
+        };
+        if start > end {
+            return Err(format!("'{}' runs backwards; list the days instead", item));
+        }
+        let step = match step {
+            Some(step) => step.parse::<usize>().ok().filter(|s| *s > 0)
+                .ok_or_else(|| format!("'{}' is not a valid step", step))?,
+            None => 1,
+        };
+
+        let mut days: Vec<usize> = (start..=end).step_by(step).map(|day| day % 7).collect();
+        days.sort_unstable();
+        days.dedup();
+        Ok(days.iter().map(|day| DAY_NAMES[*day]).collect::<Vec<_>>().join(","))
+    });
+    Ok(items.collect::<Result<Vec<_>, String>>()?.join(","))
+}
+
+pub struct SchedulerService;
+
+impl SchedulerService {
+    pub fn list<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<ScheduleEntry>, String> {
+        Ok(entries(&SettingsService::load(app)?))
+    }
+
+    pub fn add<R: Runtime>(app: &AppHandle<R>, target: ScheduleTarget, rule: ScheduleRule) -> Result<Vec<ScheduleEntry>, String> {
+        let settings = SettingsService::modify(app, |settings| {
+            // Millisecond timestamps are unique enough for something added by hand.
+            let mut id = format!("{:x}", Local::now().timestamp_millis());
+            while settings.access_schedules.iter().any(|s| s.id == id) {
+                id.push('0');
+            }
+            settings.access_schedules.push(AccessSchedule { id, target, rule, enabled: true });
+            Ok(())
+        })?;
+        Ok(entries(&settings))
+    }
+
+    pub fn remove<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<Vec<ScheduleEntry>, String> {
+        let settings = SettingsService::modify(app, |settings| {
+            settings.access_schedules.retain(|s| s.id != id);
+            Ok(())
+        })?;
+        Ok(entries(&settings))
+    }
+
+    pub fn set_enabled<R: Runtime>(app: &AppHandle<R>, id: &str, enabled: bool) -> Result<Vec<ScheduleEntry>, String> {
+        let settings = SettingsService::modify(app, |settings| {
+            let schedule = settings.access_schedules.iter_mut()
+                .find(|s| s.id == id)
+                .ok_or_else(|| format!("No schedule '{}'", id))?;
+            schedule.enabled = enabled;
+            Ok(())
+        })?;
+        Ok(entries(&settings))
+    }
+
+    /// Next runs of a rule that has not been saved yet, for the editor.
+    pub fn preview(rule: &ScheduleRule) -> Result<Vec<DateTime<Local>>, String> {
+        rule.validate()?;
+        Ok(rule.upcoming(Local::now(), PREVIEW_RUNS))
+    }
+
+    /// The job log, oldest first.
+    pub fn log<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<JobRecord>, String> {
+        LOG.read(app)
+    }
+
+    async fn run<R: Runtime>(app: &AppHandle<R>, schedule: &AccessSchedule, scheduled_for: DateTime<Local>) {
+        let (outcome, results, reason) = match &schedule.target {
+            ScheduleTarget::Server(server) => match ServerService::request_access(app, server).await {
+                Ok(()) => (JobOutcome::Succeeded, Vec::new(), None),
+                Err(e) => (JobOutcome::Failed, Vec::new(), Some(e)),
+            },
+            ScheduleTarget::Group(group) => match ServerService::request_group_access(app, group).await {
+                Ok(results) => {
+                    let failed = results.iter().filter(|r| r.error.is_some()).count();
+                    let outcome = match failed {
+                        0 => JobOutcome::Succeeded,
+                        n if n == results.len() => JobOutcome::Failed,
+                        _ => JobOutcome::Partial,
+                    };
+                    let reason = (failed > 0).then(|| format!("{} of {} requests failed", failed, results.len()));
+                    (outcome, results, reason)
+                }
+                Err(e) => (JobOutcome::Failed, Vec::new(), Some(e)),
+            },
+        };
+        Self::record(app, schedule, scheduled_for, outcome, results, reason);
+    }
+
+    fn record<R: Runtime>(
+        app: &AppHandle<R>,
+        schedule: &AccessSchedule,
+        scheduled_for: DateTime<Local>,
+        outcome: JobOutcome,
+        results: Vec<AccessResult>,
+        reason: Option<String>,
+    ) {
+        eprintln!("[Scheduler] '{}' ({:?}): {:?} {}", schedule.id, schedule.target, outcome, reason.as_deref().unwrap_or_default());
+
+        let record = JobRecord {
+            time: Local::now(),
+            schedule: schedule.id.clone(),
+            target: schedule.target.clone(),
+            scheduled_for,
+            outcome,
+            results,
+            reason,
+        };
+        if let Err(e) = LOG.append(app, &record) {
+            eprintln!("[Scheduler] Failed to write log: {}", e);
+        }
+        let _ = app.emit("schedule-ran", &record);
+    }
+}
+
+fn entries(settings: &Settings) -> Vec<ScheduleEntry> {
+    let now = Local::now();
+    settings.access_schedules.iter()
+        .map(|schedule| ScheduleEntry {
+            next_runs: if schedule.enabled { schedule.rule.upcoming(now, PREVIEW_RUNS) } else { Vec::new() },
+            schedule: schedule.clone(),
+        })
+        .collect()
+}
+
+/// Fires schedules while the app runs. Each pass looks at everything due since
+/// the previous pass, so runs that fell into a sleep are seen on wake-up: the
+/// latest of them fires if it is recent enough, otherwise it is logged as missed.
+/// Runs from before the app started are not caught up.
+pub fn spawn_scheduler<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let cutoff = Local::now() - ChronoDuration::days(RETENTION_DAYS);
+        match LOG.retain(&app, |record: &JobRecord| record.time >= cutoff) {
+            Ok(0) => {}
+            Ok(dropped) => eprintln!("[Scheduler] Dropped {} log records older than {} days", dropped, RETENTION_DAYS),
+            Err(e) => eprintln!("[Scheduler] Failed to prune the schedule log: {}", e),
+        }
+
+        let mut last_pass = Local::now();
+        loop {
+            let now = Local::now();
+            let schedules = match SettingsService::load(&app) {
+                Ok(settings) => settings.access_schedules,
+                Err(e) => {
+                    eprintln!("[Scheduler] {}", e);
+                    Vec::new()
+                }
+            };
+
+            for schedule in schedules.iter().filter(|s| s.enabled) {
+                let Some((scheduled_for, count)) = schedule.rule.due(last_pass, now) else {
+                    continue;
+                };
+
+                if now - scheduled_for > ChronoDuration::minutes(CATCH_UP_WINDOW_MINUTES) {
+                    let count = match count {
+                        MAX_COUNTED_RUNS => format!("{}+", count),
+                        count => count.to_string(),
+                    };
+                    let reason = format!("{} run(s) missed while the computer was asleep or busy", count);
+                    SchedulerService::record(&app, schedule, scheduled_for, JobOutcome::Missed, Vec::new(), Some(reason));
+                    continue;
+                }
+
+                let app = app.clone();
+                let schedule = schedule.clone();
+                tauri::async_runtime::spawn(async move {
+                    SchedulerService::run(&app, &schedule, scheduled_for).await;
+                });
+            }
+            last_pass = now;
+
+            let sleep = schedules.iter()
+                .filter(|s| s.enabled)
+                .filter_map(|s| s.rule.upcoming(now, 1).into_iter().next())
+                .min()
+                .map_or(MAX_SLEEP, |next| {
+                    // Already past: go round again straight away.
+                    (next - Local::now()).to_std().unwrap_or(Duration::ZERO).min(MAX_SLEEP)
+                });
+
+            let state = app.state::<AppState>();
+            tokio::select! {
+                _ = tokio::time::sleep(sleep) => {}
+                _ = state.scheduler.wakeup.notified() => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, NaiveDate, TimeZone, Weekday};
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local.from_local_datetime(&NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()).unwrap()
+    }
+
+    fn weekdays(expr: &str) -> Vec<Weekday> {
+        // 2026-01-05 is a Monday.
+        ScheduleRule::Cron(expr.into())
+            .upcoming(local(2026, 1, 4, 12, 0), 7)
+            .iter()
+            .map(|run| run.weekday())
+            .collect()
+    }
+
+    #[test]
+    fn days_of_week_are_numbered_as_in_crontab() {
+        use Weekday::*;
+        assert_eq!(weekdays("30 8 * * 1-5"), [Mon, Tue, Wed, Thu, Fri, Mon, Tue]);
+        assert_eq!(weekdays("30 8 * * Mon-Fri"), weekdays("30 8 * * 1-5"));
+        assert_eq!(weekdays("0 9 * * 0"), weekdays("0 9 * * 7"));
+        assert_eq!(weekdays("0 9 * * 0")[0], Sun);
+        assert_eq!(weekdays("0 9 * * 1,3,5")[..3], [Mon, Wed, Fri]);
+        assert_eq!(weekdays("0 9 * * 1-5/2")[..3], [Mon, Wed, Fri]);
+        assert_eq!(weekdays("0 0 9 * * 6-7")[..2], [Sat, Sun]);
+    }
+
+    #[test]
+    fn rejects_invalid_days_of_week() {
+        for expr in ["0 9 * * 8", "0 9 * * 5-1", "0 9 * * 1-5/0", "0 9 * * x"] {
+            assert!(parse_cron(expr).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn due_returns_the_latest_run_and_the_count() {
+        let rule = ScheduleRule::Cron("*/10 * * * *".into());
+        let from = local(2026, 1, 5, 8, 0);
+
+        assert_eq!(rule.due(from, local(2026, 1, 5, 8, 5)), None);
+        assert_eq!(rule.due(from, local(2026, 1, 5, 8, 10)), Some((local(2026, 1, 5, 8, 10), 1)));
+        assert_eq!(rule.due(from, local(2026, 1, 5, 9, 5)), Some((local(2026, 1, 5, 9, 0), 6)));
+    }
+
+    #[test]
+    fn due_stops_counting_after_a_long_sleep() {
+        let rule = ScheduleRule::Cron("* * * * * *".into());
+        let from = local(2026, 1, 5, 8, 0);
+        let to = local(2026, 1, 12, 8, 0);
+        assert_eq!(rule.due(from, to), Some((to, MAX_COUNTED_RUNS)));
+    }
+
+    #[test]
+    fn once_is_due_in_its_window_only() {
+        let at = local(2026, 1, 5, 8, 30);
+        let rule = ScheduleRule::Once(at);
+        assert_eq!(rule.due(local(2026, 1, 5, 8, 0), local(2026, 1, 5, 9, 0)), Some((at, 1)));
+        assert_eq!(rule.due(at, local(2026, 1, 5, 9, 0)), None);
+    }
+
+    #[test]
+    fn server_targets_must_be_valid_ids() {
+        let schedule = |target| AccessSchedule {
+            id: "s".into(),
+            target,
+            rule: ScheduleRule::Cron("0 9 * * 1-5".into()),
+            enabled: true,
+        };
+        assert!(schedule(ScheduleTarget::Server("web-01".into())).validate().is_ok());
+        assert!(schedule(ScheduleTarget::Server("../users".into())).validate().is_err());
+        assert!(schedule(ScheduleTarget::Group(" ".into())).validate().is_err());
+    }
+}
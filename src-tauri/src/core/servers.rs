@@ -0,0 +1,362 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::{Notify, Semaphore};
+use tokio::task::JoinSet;
+use crate::core::backend::{validate_server_id, AccessStatus, BackendClient, BackendError};
+use crate::core::history::{AccessHistoryService, HistoryEvent};
+use crate::core::keep_alive::KeepAliveService;
+use crate::core::notifications::NotificationService;
+use crate::core::settings::{SavedServer, SettingsService};
+use crate::state::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Access requests in flight at once when a whole group is requested.
+const GROUP_CONCURRENCY: usize = 4;
+const GROUP_NAME_MAX_LEN: usize = 64;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServerStatus {
+    Idle,
+    AccessGranted,
+    Offline,
+}
+
+/// A saved server and its last known access state.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerEntry {
+    pub id: String,
+    pub status: ServerStatus,
+    pub time_remaining: Option<String>,
+    pub expiration: Option<i64>,
+    pub ip: Option<String>,
+    pub keep_alive: bool,
+    pub groups: Vec<String>,
+}
+
+/// Outcome of one server in a group request.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessResult {
+    pub server: String,
+    pub error: Option<String>,
+}
+
+impl ServerEntry {
+    fn idle(saved: &SavedServer) -> Self {
+        Self {
+            id: saved.id.clone(),
+            status: ServerStatus::Idle,
+            time_remaining: None,
+            expiration: None,
+            ip: None,
+            keep_alive: saved.keep_alive,
+            groups: saved.groups.clone(),
+        }
+    }
+
+    /// `time_remaining` is in minutes (as the dashboard shows it); `expiration`
+    /// is the fallback for backends that only send the timestamp.
+    pub fn minutes_left(&self) -> Option<i64> {
+        self.time_remaining.as_deref()
+            .and_then(|t| t.trim().parse::<i64>().ok())
+            .or_else(|| self.expiration.map(|exp| (exp - Utc::now().timestamp()).div_euclid(60)))
+    }
+}
+
+/// Runtime state of the saved servers. The list itself is `saved_servers` in
+/// `settings.json`; the statuses only live here and are never persisted.
+#[derive(Default)]
+pub struct ServerRegistry {
+    servers: Mutex<Vec<ServerEntry>>,
+    /// Cuts the poll interval short, e.g. right after a server was added.
+    wakeup: Notify,
+}
+
+impl ServerRegistry {
+    pub fn list(&self) -> Vec<ServerEntry> {
+        self.servers.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, id: &str) -> Option<ServerEntry> {
+        self.servers.lock().unwrap().iter().find(|s| s.id == id).cloned()
+    }
+
+    pub fn wake(&self) {
+        self.wakeup.notify_one();
+    }
+}
+
+pub struct ServerService;
+
+impl ServerService {
+    /// Brings the registry in line with `saved_servers`, keeping the status of
+    /// servers that are still there. Emits `servers-changed` if anything changed.
+    pub fn reload<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<ServerEntry>, String> {
+        let saved = SettingsService::load(app)?.saved_servers;
+        let state = app.state::<AppState>();
+        let registry = &state.servers;
+
+        let (servers, changed) = {
+            let mut servers = registry.servers.lock().unwrap();
+            let updated: Vec<ServerEntry> = saved
+                .iter()
+                .map(|s| match servers.iter().find(|e| e.id == s.id) {
+                    Some(entry) => ServerEntry {
+                        keep_alive: s.keep_alive,
+                        groups: s.groups.clone(),
+                        ..entry.clone()
+                    },
+                    None => ServerEntry::idle(s),
+                })
+                .collect();
+            let changed = updated != *servers;
+            *servers = updated;
+            (servers.clone(), changed)
+        };
+
+        if changed {
+            let _ = app.emit("servers-changed", &servers);
+            registry.wake();
+        }
+        Ok(servers)
+    }
+
+    /// Adds a server after the backend confirmed it exists.
+    pub async fn add<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<Vec<ServerEntry>, String> {
+        validate_server_id(id)?;
+        if app.state::<AppState>().servers.get(id).is_some() {
+            return Err("ERR_DUPLICATE".into());
+        }
+
+        let (client, token) = BackendClient::authorized(app).await?;
+        if !client.exists(&token, id).await? {
+            return Err(BackendError::NotFound.into());
+        }
+
+        SettingsService::modify(app, |settings| {
+            if !settings.saved_servers.iter().any(|s| s.id == id) {
+                settings.saved_servers.push(SavedServer { id: id.to_string(), keep_alive: false, groups: Vec::new() });
+            }
+            Ok(())
+        })?;
+        Self::reload(app)
+    }
+
+    pub fn remove<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<Vec<ServerEntry>, String> {
+        SettingsService::modify(app, |settings| {
+            settings.saved_servers.retain(|s| s.id != id);
+            Ok(())
+        })?;
+        Self::reload(app)
+    }
+
+    pub fn set_keep_alive<R: Runtime>(app: &AppHandle<R>, id: &str, enabled: bool) -> Result<Vec<ServerEntry>, String> {
+        SettingsService::modify(app, |settings| {
+            let server = settings.saved_servers.iter_mut()
+                .find(|s| s.id == id)
+                .ok_or(BackendError::NotFound)?;
+            server.keep_alive = enabled;
+            Ok(())
+        })?;
+        Self::reload(app)
+    }
+
+    /// Replaces the groups (tags) of a server. Names are trimmed and deduplicated.
+    pub fn set_groups<R: Runtime>(app: &AppHandle<R>, id: &str, groups: Vec<String>) -> Result<Vec<ServerEntry>, String> {
+        let mut names: Vec<String> = Vec::new();
+        for group in groups {
+            let group = group.trim().to_string();
+            validate_group_name(&group)?;
+            if !names.contains(&group) {
+                names.push(group);
+            }
+        }
+
+        SettingsService::modify(app, |settings| {
+            let server = settings.saved_servers.iter_mut()
+                .find(|s| s.id == id)
+                .ok_or(BackendError::NotFound)?;
+            server.groups = names;
+            Ok(())
+        })?;
+        Self::reload(app)
+    }
+
+    /// Every group used by a saved server, sorted.
+    pub fn groups<R: Runtime>(app: &AppHandle<R>) -> Vec<String> {
+        let mut groups: Vec<String> = app.state::<AppState>().servers.list()
+            .into_iter()
+            .flat_map(|s| s.groups)
+            .collect();
+        groups.sort();
+        groups.dedup();
+        groups
+    }
+
+    /// Requests access to every server in a group, at most `GROUP_CONCURRENCY`
+    /// at a time. One failure does not stop the others; each server gets a result.
+    pub async fn request_group_access<R: Runtime>(app: &AppHandle<R>, group: &str) -> Result<Vec<AccessResult>, String> {
+        let members: Vec<String> = app.state::<AppState>().servers.list()
+            .into_iter()
+            .filter(|s| s.groups.iter().any(|g| g == group))
+            .map(|s| s.id)
+            .collect();
+        if members.is_empty() {
+            return Err(format!("No servers in group '{}'", group));
+        }
+
+        let permits = Arc::new(Semaphore::new(GROUP_CONCURRENCY));
+        let mut requests = JoinSet::new();
+        for server in members.iter().cloned() {
+            let app = app.clone();
+            let permits = permits.clone();
+            requests.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let error = Self::request_access(&app, &server).await.err();
+                AccessResult { server, error }
+            });
+        }
+
+        let mut results = Vec::with_capacity(members.len());
+        while let Some(joined) = requests.join_next().await {
+            match joined {
+                Ok(result) => results.push(result),
+                Err(e) => eprintln!("[Servers] Group request task failed: {}", e),
+            }
+        }
+        // Completion order is random; report in dashboard order.
+        results.sort_by_key(|r| members.iter().position(|m| *m == r.server));
+        Ok(results)
+    }
+
+    /// Requests access and refreshes the server's status right away. The id is
+    /// checked here rather than by each caller, since it ends up in the URL path.
+    pub async fn request_access<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<(), String> {
+        validate_server_id(id)?;
+        let result = Self::send_request(app, id).await;
+
+        let entry = app.state::<AppState>().servers.get(id).unwrap_or_else(|| {
+            ServerEntry::idle(&SavedServer { id: id.to_string(), keep_alive: false, groups: Vec::new() })
+        });
+        match &result {
+            Ok(()) => AccessHistoryService::record(app, &entry, HistoryEvent::Requested, None),
+            Err(e) => AccessHistoryService::record(app, &entry, HistoryEvent::Failed, Some(e.clone())),
+        }
+
+        result?;
+        Self::check(app, id).await;
+        Ok(())
+    }
+
+    async fn send_request<R: Runtime>(app: &AppHandle<R>, id: &str) -> Result<(), String> {
+        let (client, token) = BackendClient::authorized(app).await?;
+        client.access(&token, id).await?;
+        Ok(())
+    }
+
+    /// Polls every saved server in parallel.
+    pub async fn sync_all<R: Runtime>(app: &AppHandle<R>) {
+        let ids: Vec<String> = app.state::<AppState>().servers.list().into_iter().map(|s| s.id).collect();
+
+        let mut checks = JoinSet::new();
+        for id in ids {
+            let app = app.clone();
+            checks.spawn(async move { Self::check(&app, &id).await });
+        }
+        while checks.join_next().await.is_some() {}
+    }
+
+    async fn check<R: Runtime>(app: &AppHandle<R>, id: &str) {
+        let result = match BackendClient::authorized(app).await {
+            Ok((client, token)) => client.status(&token, id).await,
+            Err(e) => Err(e),
+        };
+        Self::update(app, id, result);
+    }
+
+    /// Records a poll result and emits `server-status-changed` when it differs.
+    /// Expiry warnings and keep-alive renewals are checked either way.
+    fn update<R: Runtime>(app: &AppHandle<R>, id: &str, result: Result<AccessStatus, BackendError>) {
+        let state = app.state::<AppState>();
+        let registry = &state.servers;
+
+        let signed_out = matches!(result, Err(BackendError::Auth));
+        let (previous, next) = {
+            let mut servers = registry.servers.lock().unwrap();
+            // Gone if it was removed while the request was in flight.
+            let Some(entry) = servers.iter_mut().find(|s| s.id == id) else {
+                return;
+            };
+
+            let idle = ServerEntry {
+                status: ServerStatus::Idle,
+                time_remaining: None,
+                expiration: None,
+                ip: None,
+                ..entry.clone()
+            };
+            let next = match result {
+                Ok(status) if status.is_active => ServerEntry {
+                    status: ServerStatus::AccessGranted,
+                    time_remaining: status.time_remaining,
+                    expiration: status.expiration,
+                    ip: status.ip,
+                    ..idle
+                },
+                Ok(_) | Err(BackendError::Auth) => idle,
+                Err(BackendError::Network | BackendError::Offline) => ServerEntry {
+                    status: ServerStatus::Offline,
+                    ..idle
+                },
+                Err(e) => {
+                    eprintln!("[Servers] Status check for '{}' failed: {}", id, e);
+                    idle
+                }
+            };
+
+            (std::mem::replace(entry, next.clone()), next)
+        };
+
+        NotificationService::status_polled(app, &next, signed_out);
+        KeepAliveService::status_polled(app, &next);
+        if previous == next {
+            return;
+        }
+        AccessHistoryService::access_changed(app, &previous, &next);
+        let _ = app.emit("server-status-changed", &next);
+    }
+}
+
+fn validate_group_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.len() > GROUP_NAME_MAX_LEN {
+        return Err(format!("Group names must be 1 to {} characters", GROUP_NAME_MAX_LEN));
+    }
+    if name.contains(',') {
+        return Err(format!("Group name '{}' must not contain a comma", name));
+    }
+    Ok(())
+}
+
+/// Keeps the access state current whether or not the dashboard is open.
+pub fn spawn_status_poller<R: Runtime>(app: AppHandle<R>) {
+    if let Err(e) = ServerService::reload(&app) {
+        eprintln!("[Servers] Failed to load saved servers: {}", e);
+    }
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            ServerService::sync_all(&app).await;
+
+            let state = app.state::<AppState>();
+            tokio::select! {
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                _ = state.servers.wakeup.notified() => {}
+            }
+        }
+    });
+}
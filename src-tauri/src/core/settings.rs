@@ -0,0 +1,474 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tauri::{AppHandle, Manager, Runtime, Url};
+use tauri_plugin_global_shortcut::Shortcut;
+use crate::core::backend::validate_server_id;
+use crate::core::keep_alive;
+use crate::core::oidc::OidcConfig;
+use crate::core::profiles::{Profile, DEFAULT_PROFILE};
+use crate::core::proxy::{ProxyMode, ProxySettings};
+use crate::core::scheduler::AccessSchedule;
+use crate::state::AppState;
+
+const SETTINGS_FILE: &str = "settings.json";
+pub const SCHEMA_VERSION: u32 = 1;
+pub const DEFAULT_SERVER_URL: &str = "https://api.myapp.com";
+
+pub const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 300;
+const REFRESH_INTERVAL_RANGE: std::ops::RangeInclusive<u64> = 60..=3600;
+
+pub const DEFAULT_DASHBOARD_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+
+pub const DEFAULT_KEEP_ALIVE_DAILY_LIMIT: u32 = 10;
+pub const DEFAULT_KEEP_ALIVE_HOURS: &str = "08:00-18:00";
+const KEEP_ALIVE_DAILY_LIMIT_MAX: u32 = 100;
+
+pub const DEFAULT_EXPIRY_WARNING_MINUTES: [u32; 2] = [15, 5];
+const EXPIRY_WARNING_RANGE: std::ops::RangeInclusive<u32> = 1..=1440;
+
+/// Keys the settings page writes through `update_settings`. Everything else
+/// has its own command (servers, profiles, TLS, proxy, schedules) that checks
+/// its input, or is not meant to be changed from the UI at all.
+pub const UI_SETTINGS: [&str; 10] = [
+    "server_url",
+    "username",
+    "extensions_enabled",
+    "expiry_notifications",
+    "expiry_warning_minutes",
+    "keep_alive_daily_limit",
+    "keep_alive_hours",
+    "keep_alive_weekdays_only",
+    "dashboard_shortcut",
+    "server_shortcuts",
+];
+
+/// A global shortcut that requests access to a favourite server.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ServerShortcut {
+    pub shortcut: String,
+    pub server: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SavedServer {
+    pub id: String,
+    /// Renew access shortly before it expires (see `core::keep_alive`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub keep_alive: bool,
+    /// Groups (tags) for requesting access to several servers at once.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+}
+
+/// Everything in `settings.json`. Field names are the keys in the file, which
+/// is why this struct is not camelCase like the other UI-facing types.
+/// Keys this version does not know about are kept in `extra` and written back.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub schema_version: u32,
+
+    // Active profile; mirrored into `profiles` by ProfileService.
+    pub server_url: String,
+    pub username: Option<String>,
+    pub oidc_issuer: Option<String>,
+    pub oidc_client_id: Option<String>,
+    pub active_profile: String,
+    pub profiles: Vec<Profile>,
+
+    // Token verification overrides.
+    pub jwks_url: Option<String>,
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+
+    pub refresh_interval_secs: u64,
+    pub extensions_enabled: bool,
+    pub saved_servers: Vec<SavedServer>,
+
+    // Desktop notifications before (and when) access runs out.
+    pub expiry_notifications: bool,
+    pub expiry_warning_minutes: Vec<u32>,
+
+    // Renewal of servers marked keep-alive. No hours means any time of day.
+    pub keep_alive_daily_limit: u32,
+    pub keep_alive_hours: Option<String>,
+    pub keep_alive_weekdays_only: bool,
+
+    // Global shortcuts, in accelerator syntax ("CommandOrControl+Shift+Space").
+    pub dashboard_shortcut: Option<String>,
+    pub server_shortcuts: Vec<ServerShortcut>,
+
+    // Access requests fired on a cron rule or at a fixed time (see `core::scheduler`).
+    pub access_schedules: Vec<AccessSchedule>,
+
+    pub tls_ca_bundle: Option<String>,
+    pub tls_client_cert: Option<String>,
+    pub tls_pinned_spki: Vec<String>,
+
+    pub proxy_mode: ProxyMode,
+    pub proxy_url: Option<String>,
+    pub proxy_no_proxy: Option<String>,
+
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            server_url: DEFAULT_SERVER_URL.to_string(),
+            username: None,
+            oidc_issuer: None,
+            oidc_client_id: None,
+            active_profile: DEFAULT_PROFILE.to_string(),
+            profiles: Vec::new(),
+            jwks_url: None,
+            jwt_issuer: None,
+            jwt_audience: None,
+            refresh_interval_secs: DEFAULT_REFRESH_INTERVAL_SECS,
+            extensions_enabled: false,
+            saved_servers: Vec::new(),
+            expiry_notifications: true,
+            expiry_warning_minutes: DEFAULT_EXPIRY_WARNING_MINUTES.to_vec(),
+            keep_alive_daily_limit: DEFAULT_KEEP_ALIVE_DAILY_LIMIT,
+            keep_alive_hours: Some(DEFAULT_KEEP_ALIVE_HOURS.to_string()),
+            keep_alive_weekdays_only: true,
+            dashboard_shortcut: Some(DEFAULT_DASHBOARD_SHORTCUT.to_string()),
+            server_shortcuts: Vec::new(),
+            access_schedules: Vec::new(),
+            tls_ca_bundle: None,
+            tls_client_cert: None,
+            tls_pinned_spki: Vec::new(),
+            proxy_mode: ProxyMode::default(),
+            proxy_url: None,
+            proxy_no_proxy: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_url("server_url", &self.server_url)?;
+        for (key, value) in [
+            ("oidc_issuer", &self.oidc_issuer),
+            ("jwks_url", &self.jwks_url),
+        ] {
+            if let Some(url) = value {
+                validate_url(key, url)?;
+            }
+        }
+
+        if !REFRESH_INTERVAL_RANGE.contains(&self.refresh_interval_secs) {
+            return Err(format!(
+                "refresh_interval_secs must be between {} and {}",
+                REFRESH_INTERVAL_RANGE.start(),
+                REFRESH_INTERVAL_RANGE.end()
+            ));
+        }
+
+        for server in &self.saved_servers {
+            validate_server_id(&server.id).map_err(|e| format!("saved_servers: {}", e))?;
+        }
+
+        if self.expiry_warning_minutes.iter().any(|m| !EXPIRY_WARNING_RANGE.contains(m)) {
+            return Err(format!(
+                "expiry_warning_minutes must be between {} and {}",
+                EXPIRY_WARNING_RANGE.start(),
+                EXPIRY_WARNING_RANGE.end()
+            ));
+        }
+
+        if self.keep_alive_daily_limit > KEEP_ALIVE_DAILY_LIMIT_MAX {
+            return Err(format!("keep_alive_daily_limit must be at most {}", KEEP_ALIVE_DAILY_LIMIT_MAX));
+        }
+        if let Some(hours) = &self.keep_alive_hours {
+            keep_alive::parse_hours(hours).map_err(|e| format!("keep_alive_hours: {}", e))?;
+        }
+
+        self.validate_shortcuts()?;
+
+        for (i, schedule) in self.access_schedules.iter().enumerate() {
+            if self.access_schedules[..i].iter().any(|s| s.id == schedule.id) {
+                return Err(format!("access_schedules contains '{}' twice", schedule.id));
+            }
+            schedule.validate().map_err(|e| format!("access_schedules: {}", e))?;
+        }
+
+        self.proxy().validate()
+    }
+
+    fn validate_shortcuts(&self) -> Result<(), String> {
+        let mut seen = Vec::new();
+        let shortcuts = self.dashboard_shortcut.iter()
+            .chain(self.server_shortcuts.iter().map(|s| &s.shortcut));
+
+        for shortcut in shortcuts {
+            let parsed = Shortcut::from_str(shortcut)
+                .map_err(|e| format!("'{}' is not a valid shortcut: {}", shortcut, e))?;
+            if seen.contains(&parsed) {
+                return Err(format!("Shortcut '{}' is used twice", shortcut));
+            }
+            seen.push(parsed);
+        }
+
+        if self.server_shortcuts.iter().any(|s| s.server.trim().is_empty()) {
+            return Err("server_shortcuts contains an entry without a server".into());
+        }
+        Ok(())
+    }
+
+    /// SSO is configured when both the issuer and the client id are set.
+    pub fn oidc(&self) -> Option<OidcConfig> {
+        Some(OidcConfig {
+            issuer: self.oidc_issuer.clone().filter(|s| !s.is_empty())?,
+            client_id: self.oidc_client_id.clone().filter(|s| !s.is_empty())?,
+            scope: None,
+        })
+    }
+
+    pub fn proxy(&self) -> ProxySettings {
+        ProxySettings {
+            mode: self.proxy_mode,
+            url: self.proxy_url.clone(),
+            no_proxy: self.proxy_no_proxy.clone(),
+            username: None,
+            has_password: false,
+        }
+    }
+}
+
+/// Owns `settings.json` in the app data directory (the same file the store
+/// plugin used, so existing installs keep their settings).
+pub struct SettingsService;
+
+impl SettingsService {
+    /// The effective settings: the user's file with the admin policy laid over it.
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Settings, String> {
+        let mut json = Self::read(app)?;
+        app.state::<AppState>().policy.apply(&mut json);
+        parse(json)
+    }
+
+    /// Read-modify-write under the settings lock. The result is validated before
+    /// it is written, and changes to keys locked by the policy are refused.
+    pub fn modify<R: Runtime, F>(app: &AppHandle<R>, change: F) -> Result<Settings, String>
+    where
+        F: FnOnce(&mut Settings) -> Result<(), String>,
+    {
+        let state = app.state::<AppState>();
+        let _guard = state.settings_lock.lock().unwrap();
+
+        // Already under the lock; a pending migration is written with the change.
+        let (user, _) = Self::read_file(app)?;
+        let mut json = user.clone();
+        state.policy.apply(&mut json);
+
+        let mut settings = parse(json)?;
+        change(&mut settings)?;
+        settings.validate()?;
+
+        let mut json = to_map(&settings)?;
+        state.policy.restore(&mut json, &user)?;
+        Self::write(app, &json)?;
+        Ok(settings)
+    }
+
+    /// Applies a partial update: top-level keys in `patch` replace the current
+    /// values, everything else is left alone. Keys outside `allowed` are refused.
+    pub fn update<R: Runtime>(app: &AppHandle<R>, patch: Map<String, Value>, allowed: &[&str]) -> Result<Settings, String> {
+        Self::modify(app, |settings| apply_patch(settings, patch, allowed))
+    }
+
+    pub fn path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+        Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join(SETTINGS_FILE))
+    }
+
+    /// The user's own settings, migrated to the current schema. Writing the
+    /// migrated file takes the settings lock so it cannot race `modify`.
+    fn read<R: Runtime>(app: &AppHandle<R>) -> Result<Map<String, Value>, String> {
+        let (json, migrated) = Self::read_file(app)?;
+        if !migrated {
+            return Ok(json);
+        }
+
+        let state = app.state::<AppState>();
+        let _guard = state.settings_lock.lock().unwrap();
+        // Read again: another caller may have changed or migrated the file meanwhile.
+        let (json, migrated) = Self::read_file(app)?;
+        if migrated {
+            Self::write(app, &json)?;
+        }
+        Ok(json)
+    }
+
+    /// Reads and migrates `settings.json` without writing it. The flag tells
+    /// whether the migrated settings still have to be saved.
+    fn read_file<R: Runtime>(app: &AppHandle<R>) -> Result<(Map<String, Value>, bool), String> {
+        let path = Self::path(app)?;
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((to_map(&Settings::default())?, false)),
+            Err(e) => return Err(format!("Failed to read settings: {}", e)),
+        };
+
+        let mut json: Map<String, Value> = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse settings: {}", e))?;
+        if migrate(&mut json)? {
+            return Ok((to_map(&parse(json)?)?, true));
+        }
+        Ok((json, false))
+    }
+
+    /// Writes to a temporary file next to the target and renames it over the
+    /// old one, so a crash mid-write never leaves a truncated settings file.
+    fn write<R: Runtime>(app: &AppHandle<R>, settings: &Map<String, Value>) -> Result<(), String> {
+        let path = Self::path(app)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        let json = serde_json::to_vec_pretty(settings).map_err(|e| e.to_string())?;
+        let tmp = path.with_extension("json.tmp");
+
+        let mut file = std::fs::File::create(&tmp).map_err(|e| format!("Failed to write settings: {}", e))?;
+        file.write_all(&json).map_err(|e| format!("Failed to write settings: {}", e))?;
+        file.sync_all().map_err(|e| format!("Failed to write settings: {}", e))?;
+        drop(file);
+
+        std::fs::rename(&tmp, &path).map_err(|e| format!("Failed to write settings: {}", e))
+    }
+}
+
+fn apply_patch(settings: &mut Settings, patch: Map<String, Value>, allowed: &[&str]) -> Result<(), String> {
+    if let Some(key) = patch.keys().find(|key| !allowed.contains(&key.as_str())) {
+        return Err(format!("'{}' cannot be changed here", key));
+    }
+    let mut json = to_map(settings)?;
+    json.extend(patch);
+    *settings = parse(json)?;
+    Ok(())
+}
+
+pub fn parse(json: Map<String, Value>) -> Result<Settings, String> {
+    serde_json::from_value(Value::Object(json)).map_err(|e| format!("Invalid settings: {}", e))
+}
+
+pub fn to_map(settings: &Settings) -> Result<Map<String, Value>, String> {
+    match serde_json::to_value(settings).map_err(|e| e.to_string())? {
+        Value::Object(map) => Ok(map),
+        _ => unreachable!("Settings always serializes to an object"),
+    }
+}
+
+/// Brings an older file up to `SCHEMA_VERSION` in place. Returns whether anything changed.
+fn migrate(json: &mut Map<String, Value>) -> Result<bool, String> {
+    let version = json.get("schema_version").and_then(Value::as_u64).unwrap_or(0) as u32;
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "settings.json was written by a newer version (schema {}); please update ServeMe",
+            version
+        ));
+    }
+
+    if version < 1 {
+        migrate_v0_to_v1(json);
+    }
+
+    json.insert("schema_version".into(), SCHEMA_VERSION.into());
+    Ok(version != SCHEMA_VERSION)
+}
+
+/// Version 0 is whatever the store plugin wrote: `null` for cleared values and
+/// server cards that still carry their last runtime status.
+fn migrate_v0_to_v1(json: &mut Map<String, Value>) {
+    json.retain(|_, value| !value.is_null());
+
+    if let Some(Value::Array(servers)) = json.get_mut("saved_servers") {
+        for server in servers.iter_mut() {
+            if let Value::Object(card) = server {
+                card.retain(|key, _| key == "id");
+            }
+        }
+    }
+}
+
+pub fn validate_url(key: &str, value: &str) -> Result<(), String> {
+    let url = Url::parse(value).map_err(|e| format!("{} is not a valid URL: {}", key, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("{} must start with http:// or https://", key));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn patch_only_touches_allowed_keys() {
+        let mut settings = Settings::default();
+        apply_patch(&mut settings, patch(json!({ "keep_alive_daily_limit": 3 })), &UI_SETTINGS).unwrap();
+        assert_eq!(settings.keep_alive_daily_limit, 3);
+
+        for key in ["tls_pinned_spki", "jwks_url", "saved_servers", "schema_version", "unknown"] {
+            let result = apply_patch(&mut settings, patch(json!({ key: [] })), &UI_SETTINGS);
+            assert!(result.is_err(), "{}", key);
+        }
+        assert_eq!(settings, Settings { keep_alive_daily_limit: 3, ..Settings::default() });
+    }
+
+    #[test]
+    fn saved_server_ids_are_validated() {
+        let mut settings = Settings::default();
+        settings.saved_servers.push(SavedServer { id: "web-01.example".into(), keep_alive: false, groups: Vec::new() });
+        assert!(settings.validate().is_ok());
+
+        settings.saved_servers.push(SavedServer { id: "../admin".into(), keep_alive: false, groups: Vec::new() });
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn v0_files_are_migrated() {
+        let mut json = patch(json!({
+            "server_url": "https://corp.example",
+            "username": null,
+            "saved_servers": [{ "id": "web-01", "status": "granted", "expiresAt": 1700000000 }]
+        }));
+        assert!(migrate(&mut json).unwrap());
+
+        assert_eq!(json["schema_version"], json!(SCHEMA_VERSION));
+        assert!(!json.contains_key("username"));
+        assert_eq!(json["saved_servers"], json!([{ "id": "web-01" }]));
+
+        let settings = parse(json).unwrap();
+        assert_eq!(settings.server_url, "https://corp.example");
+        assert_eq!(settings.saved_servers, vec![SavedServer { id: "web-01".into(), keep_alive: false, groups: Vec::new() }]);
+    }
+
+    #[test]
+    fn current_files_are_left_alone() {
+        let mut json = to_map(&Settings::default()).unwrap();
+        let before = json.clone();
+        assert!(!migrate(&mut json).unwrap());
+        assert_eq!(json, before);
+    }
+
+    #[test]
+    fn newer_files_are_rejected() {
+        let mut json = patch(json!({ "schema_version": SCHEMA_VERSION + 1 }));
+        assert!(migrate(&mut json).is_err());
+    }
+}
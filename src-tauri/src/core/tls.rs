@@ -0,0 +1,272 @@
+use base64::{engine::general_purpose, Engine as _};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tauri::{AppHandle, Runtime};
+use x509_parser::prelude::{FromDer, X509Certificate};
+use crate::core::keychain::KeychainService;
+use crate::core::settings::SettingsService;
+
+const PIN_PREFIX: &str = "sha256/";
+
+/// TLS options for backend traffic. Paths point at PEM files; the client
+/// private key is never written to settings and lives in the keychain.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsSettings {
+    #[serde(default)]
+    pub ca_bundle: Option<String>,
+    #[serde(default)]
+    pub client_cert: Option<String>,
+    /// Base64 SHA-256 hashes of acceptable SubjectPublicKeyInfo blocks,
+    /// optionally prefixed with `sha256/` as curl writes them.
+    #[serde(default)]
+    pub pinned_spki: Vec<String>,
+    #[serde(default)]
+    pub has_client_key: bool,
+    /// Host of the configured backend. Pins only apply to connections to
+    /// this host, so the identity provider keeps its own certificates.
+    #[serde(skip)]
+    pub pinned_host: Option<String>,
+}
+
+impl TlsSettings {
+    pub fn load<R: Runtime>(app: &AppHandle<R>) -> Result<Self, String> {
+        let settings = SettingsService::load(app)?;
+        Ok(Self {
+            ca_bundle: settings.tls_ca_bundle.filter(|s| !s.is_empty()),
+            client_cert: settings.tls_client_cert.filter(|s| !s.is_empty()),
+            pinned_spki: settings.tls_pinned_spki,
+            has_client_key: KeychainService::get_client_key()?.is_some(),
+            pinned_host: backend_host(&settings.server_url),
+        })
+    }
+
+    pub fn save<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), String> {
+        SettingsService::modify(app, |settings| {
+            settings.tls_ca_bundle = self.ca_bundle.clone();
+            settings.tls_client_cert = self.client_cert.clone();
+            settings.tls_pinned_spki = self.pinned_spki.clone();
+            Ok(())
+        })
+        .map(|_| ())
+    }
+
+    /// Takes the backend host from these settings, for settings sent by the UI.
+    pub fn with_pinned_host(self, pinned_host: Option<String>) -> Self {
+        Self { pinned_host, ..self }
+    }
+
+    fn is_default(&self) -> bool {
+        self.ca_bundle.is_none() && self.client_cert.is_none() && self.pinned_spki.is_empty()
+    }
+}
+
+/// Applies the TLS settings to a client builder. Without any settings the
+/// reqwest defaults (platform roots, no client auth) are left untouched.
+pub fn apply(
+    builder: reqwest::ClientBuilder,
+    settings: &TlsSettings,
+    client_key: Option<&str>,
+) -> Result<reqwest::ClientBuilder, String> {
+    if settings.is_default() {
+        return Ok(builder);
+    }
+
+    let provider = CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+
+    let extra_roots = match &settings.ca_bundle {
+        Some(path) => CertificateDer::pem_file_iter(path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("Failed to read CA bundle {}: {}", path, e))?,
+        None => Vec::new(),
+    };
+
+    let platform = rustls_platform_verifier::Verifier::new_with_extra_roots(extra_roots, provider.clone())
+        .map_err(|e| format!("Failed to load trust roots: {}", e))?;
+    let verifier = PinningVerifier {
+        inner: Arc::new(platform),
+        pins: parse_pins(&settings.pinned_spki)?,
+        pinned_host: settings.pinned_host.clone(),
+    };
+
+    let config_builder = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier));
+
+    let mut config = match (&settings.client_cert, client_key) {
+        (Some(path), Some(key)) => {
+            let certs = CertificateDer::pem_file_iter(path)
+                .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+                .map_err(|e| format!("Failed to read client certificate {}: {}", path, e))?;
+            let key = PrivateKeyDer::from_pem_slice(key.as_bytes())
+                .map_err(|e| format!("Invalid client key: {}", e))?;
+            config_builder
+                .with_client_auth_cert(certs, key)
+                .map_err(|e| format!("Client certificate does not match its key: {}", e))?
+        }
+        (Some(_), None) => return Err("A client certificate is set but no client key was imported".into()),
+        (None, _) => config_builder.with_no_client_auth(),
+    };
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(builder.tls_backend_preconfigured(config))
+}
+
+/// Checks that a PEM file holds a private key, returning its contents so it can go into the keychain.
+pub fn read_client_key(path: &str) -> Result<String, String> {
+    let pem = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    PrivateKeyDer::from_pem_slice(pem.as_bytes()).map_err(|e| format!("Invalid client key: {}", e))?;
+    Ok(pem)
+}
+
+fn backend_host(server_url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(server_url).ok()?;
+    url.host_str().map(|host| host.trim_matches(['[', ']']).to_ascii_lowercase())
+}
+
+fn parse_pins(pins: &[String]) -> Result<Vec<Vec<u8>>, String> {
+    pins.iter()
+        .map(|pin| {
+            let encoded = pin.trim().trim_start_matches(PIN_PREFIX);
+            match general_purpose::STANDARD.decode(encoded) {
+                Ok(hash) if hash.len() == 32 => Ok(hash),
+                _ => Err(format!("Invalid SPKI pin '{}': expected a base64 SHA-256 hash", pin)),
+            }
+        })
+        .collect()
+}
+
+/// Runs the normal chain validation first, then requires one certificate in
+/// the chain to carry a pinned public key. Pinning an intermediate or the CA
+/// keeps working across leaf certificate renewals. Other hosts sharing the
+/// client only get the chain validation.
+#[derive(Debug)]
+struct PinningVerifier {
+    inner: Arc<dyn ServerCertVerifier>,
+    pins: Vec<Vec<u8>>,
+    pinned_host: Option<String>,
+}
+
+impl PinningVerifier {
+    fn is_pinned(&self, server_name: &ServerName<'_>) -> bool {
+        let Some(host) = &self.pinned_host else {
+            return false;
+        };
+        match server_name {
+            ServerName::DnsName(name) => name.as_ref().eq_ignore_ascii_case(host),
+            ServerName::IpAddress(ip) => host.parse::<std::net::IpAddr>().is_ok_and(|h| std::net::IpAddr::from(*ip) == h),
+            _ => false,
+        }
+    }
+}
+
+impl ServerCertVerifier for PinningVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now)?;
+        if self.pins.is_empty() || !self.is_pinned(server_name) {
+            return Ok(verified);
+        }
+
+        let pinned = std::iter::once(end_entity)
+            .chain(intermediates)
+            .filter_map(spki_hash)
+            .any(|hash| self.pins.contains(&hash));
+
+        if pinned {
+            Ok(verified)
+        } else {
+            eprintln!("[TLS] No certificate for {:?} matched a pinned key", server_name);
+            Err(rustls::Error::General("certificate does not match any pinned public key".into()))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+fn spki_hash(cert: &CertificateDer<'_>) -> Option<Vec<u8>> {
+    let (_, parsed) = X509Certificate::from_der(cert.as_ref()).ok()?;
+    Some(Sha256::digest(parsed.public_key().raw).to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verifier(server_url: &str) -> PinningVerifier {
+        let provider = Arc::new(rustls::crypto::aws_lc_rs::default_provider());
+        let roots = Arc::new(rustls::RootCertStore::empty());
+        PinningVerifier {
+            inner: rustls::client::WebPkiServerVerifier::builder_with_provider(roots, provider).build().unwrap(),
+            pins: vec![vec![0; 32]],
+            pinned_host: backend_host(server_url),
+        }
+    }
+
+    #[test]
+    fn pins_only_apply_to_the_backend_host() {
+        let verifier = verifier("https://Access.Example.com:8443/api");
+
+        assert!(verifier.is_pinned(&ServerName::try_from("access.example.com").unwrap()));
+        assert!(!verifier.is_pinned(&ServerName::try_from("login.example.com").unwrap()));
+        assert!(!verifier.is_pinned(&ServerName::try_from("access.example.com.evil.test").unwrap()));
+    }
+
+    #[test]
+    fn pins_match_ip_backends() {
+        let v4 = verifier("https://10.0.0.5/");
+        assert!(v4.is_pinned(&ServerName::try_from("10.0.0.5").unwrap()));
+        assert!(!v4.is_pinned(&ServerName::try_from("10.0.0.6").unwrap()));
+
+        let v6 = verifier("https://[::1]:8443/");
+        assert!(v6.is_pinned(&ServerName::try_from("::1").unwrap()));
+    }
+
+    #[test]
+    fn nothing_is_pinned_without_a_backend() {
+        assert!(!verifier("").is_pinned(&ServerName::try_from("access.example.com").unwrap()));
+    }
+
+    #[test]
+    fn pins_accept_curl_format() {
+        let pin = format!("sha256/{}", general_purpose::STANDARD.encode([7u8; 32]));
+        assert_eq!(parse_pins(&[pin]).unwrap(), vec![vec![7u8; 32]]);
+        assert!(parse_pins(&["sha256/AAAA".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::mpsc;
+use crate::api::extensions::{cleanup_processes, restart_extension};
+use crate::core::backend;
+use crate::core::servers::ServerService;
+use crate::core::settings::{Settings, SettingsService};
+use crate::state::AppState;
+use crate::ui::events::shortcut;
+
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Keys that decide which backend (and which session) the refresh loop talks to.
+const TARGET_KEYS: [&str; 5] = ["server_url", "active_profile", "username", "oidc_issuer", "oidc_client_id"];
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChange {
+    pub changed: Vec<String>,
+    pub settings: Settings,
+}
+
+/// Watches `settings.json` and propagates every change, whoever made it: the UI
+/// (through `SettingsService`), a hand edit or a provisioning tool.
+///
+/// The directory is watched rather than the file, because atomic writes replace
+/// the file and a watch on the old inode would go quiet after the first save.
+pub fn spawn_settings_watcher<R: Runtime>(app: AppHandle<R>) {
+    let path = match SettingsService::path(&app) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("[Settings Watcher] {}", e);
+            return;
+        }
+    };
+    let Some(dir) = path.parent().map(|d| d.to_path_buf()) else {
+        return;
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!("[Settings Watcher] Failed to create {}: {}", dir.display(), e);
+        return;
+    }
+
+    *app.state::<AppState>().settings_snapshot.lock().unwrap() = SettingsService::load(&app).ok();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let target = path.clone();
+    let watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            if event.paths.iter().any(|p| *p == target) {
+                let _ = tx.send(());
+            }
+        }
+    });
+
+    let mut watcher = match watcher {
+        Ok(w) => w,
+        Err(e) => {
+            eprintln!("[Settings Watcher] Failed to start: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+        eprintln!("[Settings Watcher] Failed to watch {}: {}", dir.display(), e);
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        // The watcher stops when dropped, so it lives as long as this task.
+        let _watcher = watcher;
+
+        while rx.recv().await.is_some() {
+            // Editors and our own rename produce bursts of events; handle them once.
+            tokio::time::sleep(DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+
+            reload(&app);
+        }
+    });
+}
+
+fn reload<R: Runtime>(app: &AppHandle<R>) {
+    let settings = match SettingsService::load(app) {
+        Ok(s) => s,
+        Err(e) => {
+            // Keep running on the last good settings until the file is fixed.
+            eprintln!("[Settings Watcher] Ignoring invalid settings.json: {}", e);
+            return;
+        }
+    };
+
+    let state = app.state::<AppState>();
+    let previous = state.settings_snapshot.lock().unwrap().replace(settings.clone());
+    let changed = changed_keys(previous.as_ref(), &settings);
+    if changed.is_empty() {
+        return;
+    }
+
+    propagate(app, SettingsChange { changed, settings });
+}
+
+fn propagate<R: Runtime>(app: &AppHandle<R>, change: SettingsChange) {
+    let state = app.state::<AppState>();
+    let touches = |prefix: &str| change.changed.iter().any(|k| k.starts_with(prefix));
+
+    if touches("tls_") || touches("proxy_") || change.changed.iter().any(|k| k == "server_url") {
+        backend::reset_http_client(app);
+    }
+    if touches("jwt_") || change.changed.iter().any(|k| k == "jwks_url" || TARGET_KEYS.contains(&k.as_str())) {
+        state.jwks.clear();
+    }
+    if change.changed.iter().any(|k| TARGET_KEYS.contains(&k.as_str())) {
+        state.refresh_wakeup.notify_one();
+        state.servers.wake();
+    }
+    if change.changed.iter().any(|k| k == "saved_servers") {
+        if let Err(e) = ServerService::reload(app) {
+            eprintln!("[Settings Watcher] Failed to reload servers: {}", e);
+        }
+    }
+
+    if change.changed.iter().any(|k| k == "access_schedules") {
+        state.scheduler.wake();
+    }
+
+    if change.changed.iter().any(|k| k == "dashboard_shortcut" || k == "server_shortcuts") {
+        shortcut::apply(app, &change.settings);
+    }
+
+    if change.changed.iter().any(|k| k == "extensions_enabled") && !change.settings.extensions_enabled {
+        cleanup_processes(app, &state);
+    }
+
+    let _ = app.emit("settings-changed", &change);
+    state.events.publish("settings-changed", &change);
+
+    let interested: Vec<String> = state.extension_subscriptions.lock().unwrap()
+        .iter()
+        .filter(|(_, keys)| keys.iter().any(|k| change.changed.contains(k)))
+        .map(|(id, _)| id.clone())
+        .collect();
+
+    for id in interested {
+        if let Err(e) = restart_extension(app, &state, &id) {
+            eprintln!("[Settings Watcher] Failed to restart '{}': {}", id, e);
+        }
+    }
+}
+
+fn changed_keys(previous: Option<&Settings>, current: &Settings) -> Vec<String> {
+    let as_map = |settings: &Settings| match serde_json::to_value(settings) {
+        Ok(Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+
+    let current = as_map(current);
+    let previous = previous.map(as_map).unwrap_or_default();
+
+    let mut keys: Vec<String> = current.keys()
+        .chain(previous.keys())
+        .filter(|k| current.get(*k) != previous.get(*k))
+        .cloned()
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
@@ -7,21 +7,55 @@ mod ui;
 use std::sync::atomic::Ordering;
 use tauri::Manager;
 use tauri_plugin_autostart::MacosLauncher;
+use tauri_plugin_deep_link::DeepLinkExt;
 
 use state::AppState;
 use ui::definitions::WindowType;
+use ui::events::instance::{self, LaunchArgs};
 use crate::core::keychain::KeychainService;
 
+use crate::api::archive::{export_config, import_config, pick_config_archive};
 use crate::api::auth::*;
-use crate::api::extensions::{cleanup_processes, list_extensions, run_extension, stop_extension, upload_extension, delete_extension};
-use crate::api::system::{fix_autostart_path, set_dialog_status};
+use crate::api::deep_link::{confirm_deep_link, dismiss_deep_link, list_deep_links};
+use crate::api::backend::{check_connection, get_access_status, request_access, server_exists};
+use crate::api::extensions::{cleanup_processes, list_extensions, run_extension, spawn_exit_monitor, stop_extension, upload_extension, delete_extension};
+use crate::api::history::{export_access_history, query_access_history};
+use crate::api::network::{get_proxy_settings, get_tls_settings, test_proxy_settings, update_proxy_settings, update_tls_settings};
+use crate::api::profiles::{create_profile, delete_profile, get_active_profile, list_profiles, switch_profile};
+use crate::api::servers::{add_server, get_keep_alive_log, list_server_groups, list_servers, remove_server, request_group_access, request_server_access, search_servers, set_keep_alive, set_server_groups};
+use crate::api::schedules::{add_schedule, get_schedule_log, list_schedules, preview_schedule, remove_schedule, set_schedule_enabled};
+use crate::api::settings::{get_policy, get_settings, get_shortcut_failures, update_settings};
+use crate::api::system::{enforce_autostart_policy, fix_autostart_path, set_autostart, set_dialog_status};
 #[cfg(target_os = "macos")]
 use tauri::ActivationPolicy;
 use tauri::RunEvent::{Exit, ExitRequested};
 
+/// Links opened while the app runs arrive as events (forwarded by the
+/// single-instance plugin on Linux and Windows); the one that launched it, if any, via `get_current`.
+fn setup_deep_links(app: &tauri::AppHandle) {
+    // Installed packages register the scheme themselves; dev builds have to do it at runtime.
+    #[cfg(all(debug_assertions, any(target_os = "linux", windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        eprintln!("[Deep Link] Failed to register scheme: {}", e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        ui::events::deep_link::handle_urls(&handle, event.urls());
+    });
+
+    if let Ok(Some(urls)) = app.deep_link().get_current() {
+        ui::events::deep_link::handle_urls(app, urls);
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // Must be registered first: a second launch forwards its arguments and exits here.
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            instance::handle_second_instance(app, argv);
+        }))
         // Register Commands
         .invoke_handler(tauri::generate_handler![
             fix_autostart_path,
@@ -31,11 +65,61 @@ pub fn run() {
             stop_extension,
             delete_extension,
             set_dialog_status,
+            login_password,
+            logout,
+            login_sso,
+            login_device,
+            cancel_device_login,
+            refresh_session,
+            get_session_info,
             save_tokens,
             get_tokens,
             get_access_token,
             get_refresh_token,
             purge_tokens,
+            list_profiles,
+            get_active_profile,
+            create_profile,
+            switch_profile,
+            delete_profile,
+            check_connection,
+            get_access_status,
+            server_exists,
+            request_access,
+            get_tls_settings,
+            update_tls_settings,
+            get_proxy_settings,
+            update_proxy_settings,
+            test_proxy_settings,
+            get_settings,
+            update_settings,
+            get_policy,
+            get_shortcut_failures,
+            set_autostart,
+            export_config,
+            import_config,
+            pick_config_archive,
+            list_deep_links,
+            confirm_deep_link,
+            dismiss_deep_link,
+            list_servers,
+            add_server,
+            remove_server,
+            request_server_access,
+            set_keep_alive,
+            get_keep_alive_log,
+            set_server_groups,
+            list_server_groups,
+            request_group_access,
+            search_servers,
+            list_schedules,
+            add_schedule,
+            remove_schedule,
+            set_schedule_enabled,
+            preview_schedule,
+            get_schedule_log,
+            query_access_history,
+            export_access_history,
         ])
         // Manage State
         .manage(AppState::new())
@@ -44,8 +128,11 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_os::init())
         .plugin(tauri_plugin_positioner::init())
-        .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_http::init())
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_autostart::init(
             MacosLauncher::LaunchAgent,
             None,
@@ -57,13 +144,25 @@ pub fn run() {
             let handle = app.handle();
             
             let _ = KeychainService::get_or_create_master_key();
+            enforce_autostart_policy(handle);
 
             spawn_background_refresh(handle.clone());
+            core::watcher::spawn_settings_watcher(handle.clone());
+            core::servers::spawn_status_poller(handle.clone());
+            core::scheduler::spawn_scheduler(handle.clone());
+            spawn_exit_monitor(handle.clone());
 
             tauri::async_runtime::spawn(core::api::server::start_server(handle.clone()));
 
             ui::tray::setup(handle)?;
             ui::windows::create(handle, WindowType::Dashboard)?;
+            match core::settings::SettingsService::load(handle) {
+                Ok(settings) => ui::events::shortcut::apply(handle, &settings),
+                Err(e) => eprintln!("[Shortcuts] {}", e),
+            }
+
+            instance::handle_args(handle, LaunchArgs::parse(std::env::args().collect()));
+            setup_deep_links(handle);
 
             Ok(())
         })
@@ -1,12 +1,55 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::process::Child;
 use std::sync::atomic::AtomicBool;
 use std::sync::Mutex;
+use tokio::sync::Notify;
+use crate::core::api::events::LocalEventBus;
+use crate::core::backend::HttpClientCache;
+use crate::core::deep_link::DeepLinkQueue;
+use crate::core::history::AccessHistory;
+use crate::core::jwt::JwksCache;
+use crate::core::keep_alive::KeepAlive;
+use crate::core::notifications::ExpiryNotices;
+use crate::core::oidc::DeviceAuthorization;
+use crate::core::policy::Policy;
+use crate::core::scheduler::Scheduler;
+use crate::core::servers::ServerRegistry;
+use crate::core::settings::Settings;
+use crate::ui::events::shortcut::ShortcutFailure;
 
 pub struct AppState {
     pub is_quitting: AtomicBool,
     pub is_dialog_open: AtomicBool,
-    pub running_extensions: Mutex<HashMap<String, Child>>
+    pub running_extensions: Mutex<HashMap<String, Child>>,
+    /// Extensions that exited with an error since they were last started.
+    pub crashed_extensions: Mutex<HashSet<String>>,
+    pub device_login: Mutex<Option<DeviceAuthorization>>,
+    /// Set from the start of a device login, before `device_login` is known.
+    pub device_login_active: AtomicBool,
+    pub device_login_cancelled: AtomicBool,
+    pub jwks: JwksCache,
+    pub http: HttpClientCache,
+    pub settings_lock: Mutex<()>,
+    /// Admin policy, read once at startup.
+    pub policy: Policy,
+    /// Last settings seen by the watcher, used to work out which keys changed.
+    pub settings_snapshot: Mutex<Option<Settings>>,
+    /// Settings keys each running extension asked to be restarted for.
+    pub extension_subscriptions: Mutex<HashMap<String, Vec<String>>>,
+    pub events: LocalEventBus,
+    /// Cuts the background refresh sleep short when the backend changes.
+    pub refresh_wakeup: Notify,
+    pub deep_links: DeepLinkQueue,
+    pub servers: ServerRegistry,
+    pub expiry_notices: ExpiryNotices,
+    pub keep_alive: KeepAlive,
+    pub scheduler: Scheduler,
+    pub history: AccessHistory,
+    /// Shortcuts that failed to register the last time they were applied.
+    pub shortcut_failures: Mutex<Vec<ShortcutFailure>>,
+    /// Archive picked in the import dialog, waiting for preview and import.
+    pub import_archive: Mutex<Option<PathBuf>>,
 }
 
 impl AppState {
@@ -14,7 +57,27 @@ impl AppState {
         Self {
             is_quitting: AtomicBool::new(false),
             is_dialog_open: AtomicBool::new(false),
-            running_extensions: Mutex::new(HashMap::new())
+            running_extensions: Mutex::new(HashMap::new()),
+            crashed_extensions: Mutex::new(HashSet::new()),
+            device_login: Mutex::new(None),
+            device_login_active: AtomicBool::new(false),
+            device_login_cancelled: AtomicBool::new(false),
+            jwks: JwksCache::default(),
+            http: HttpClientCache::default(),
+            settings_lock: Mutex::new(()),
+            policy: Policy::load(),
+            settings_snapshot: Mutex::new(None),
+            extension_subscriptions: Mutex::new(HashMap::new()),
+            events: LocalEventBus::default(),
+            refresh_wakeup: Notify::new(),
+            deep_links: DeepLinkQueue::default(),
+            servers: ServerRegistry::default(),
+            expiry_notices: ExpiryNotices::default(),
+            keep_alive: KeepAlive::default(),
+            scheduler: Scheduler::default(),
+            history: AccessHistory::default(),
+            shortcut_failures: Mutex::new(Vec::new()),
+            import_archive: Mutex::new(None),
         }
     }
 }
\ No newline at end of file
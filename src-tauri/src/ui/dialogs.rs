@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, FileDialogBuilder, FilePath};
+use tokio::sync::oneshot;
+use crate::state::AppState;
+
+/// A file filter: the name shown in the dialog and the extensions it matches.
+pub type Filter<'a> = (&'a str, &'a [&'a str]);
+
+/// Asks where to write a file. Paths the app writes always come from a
+/// dialog opened here, never from the webview. `None` if cancelled.
+pub async fn save_file<R: Runtime>(app: &AppHandle<R>, file_name: &str, filter: Filter<'_>) -> Result<Option<PathBuf>, String> {
+    let dialog = app.dialog().file().set_file_name(file_name).add_filter(filter.0, filter.1);
+    show(app, dialog, |dialog, done| dialog.save_file(done)).await
+}
+
+/// Asks for a file to read, like `save_file`.
+pub async fn open_file<R: Runtime>(app: &AppHandle<R>, filter: Filter<'_>) -> Result<Option<PathBuf>, String> {
+    let dialog = app.dialog().file().add_filter(filter.0, filter.1);
+    show(app, dialog, |dialog, done| dialog.pick_file(done)).await
+}
+
+/// Keeps the dashboard from hiding on blur while the dialog is up.
+async fn show<R: Runtime>(
+    app: &AppHandle<R>,
+    dialog: FileDialogBuilder<R>,
+    open: impl FnOnce(FileDialogBuilder<R>, Box<dyn FnOnce(Option<FilePath>) + Send>),
+) -> Result<Option<PathBuf>, String> {
+    let state = app.state::<AppState>();
+    state.is_dialog_open.store(true, Ordering::Relaxed);
+    let (tx, rx) = oneshot::channel();
+    open(dialog, Box::new(move |path| {
+        let _ = tx.send(path);
+    }));
+    let path = rx.await.ok().flatten();
+    state.is_dialog_open.store(false, Ordering::Relaxed);
+
+    path.map(|path| path.into_path().map_err(|e| format!("Invalid file location: {}", e)))
+        .transpose()
+}
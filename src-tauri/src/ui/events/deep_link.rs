@@ -0,0 +1,37 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use crate::core::deep_link::DeepLink;
+use crate::state::AppState;
+use crate::ui::events::tray::{open_route, show_dashboard};
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RejectedLink {
+    url: String,
+    error: String,
+}
+
+/// Handles `serveme://` links from the deep-link plugin. Nothing runs here:
+/// actions are validated, queued and shown in the dashboard for confirmation.
+pub fn handle_urls(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        if let Err(error) = handle_url(app, &url) {
+            eprintln!("[Deep Link] Rejected {}: {}", url, error);
+            show_dashboard(app);
+            let _ = app.emit("deep-link-rejected", RejectedLink { url: url.to_string(), error });
+        }
+    }
+}
+
+fn handle_url(app: &AppHandle, url: &Url) -> Result<(), String> {
+    match DeepLink::parse(url)? {
+        DeepLink::Open(route) => open_route(app, route),
+        DeepLink::Action(action) => {
+            action.validate(app)?;
+            let pending = app.state::<AppState>().deep_links.push(action);
+            show_dashboard(app);
+            let _ = app.emit("deep-link-request", pending);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+use crate::core::backend::validate_server_id;
+use crate::core::deep_link::SCHEME;
+use crate::core::servers::ServerService;
+use crate::ui::events::tray::show_dashboard;
+
+/// Command-line flags, read at startup and when a second launch forwards its
+/// arguments to the running instance.
+#[derive(Debug, Default, PartialEq)]
+pub struct LaunchArgs {
+    /// `--show`: open the dashboard.
+    pub show: bool,
+    /// `--access <server>` (repeatable): request access to the server.
+    pub access: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct AccessRequested {
+    server: String,
+    error: Option<String>,
+}
+
+impl LaunchArgs {
+    /// Parses `argv`, including the program name in the first position.
+    pub fn parse(argv: Vec<String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = argv.into_iter().skip(1);
+
+        while let Some(arg) = args.next() {
+            let server = match arg.as_str() {
+                "--show" => {
+                    parsed.show = true;
+                    continue;
+                }
+                "--access" => args.next(),
+                other => other.strip_prefix("--access=").map(String::from),
+            };
+
+            match server {
+                Some(server) if validate_server_id(&server).is_ok() => parsed.access.push(server),
+                Some(server) => eprintln!("[Instance] Ignoring invalid server id '{}'", server),
+                // Links are handled by the deep-link plugin.
+                None if arg.strip_prefix(SCHEME).is_some_and(|rest| rest.starts_with("://")) => {}
+                None => eprintln!("[Instance] Ignoring argument '{}'", arg),
+            }
+        }
+        parsed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.show && self.access.is_empty()
+    }
+}
+
+/// Called by the single-instance plugin when ServeMe is launched again. A bare
+/// second launch (e.g. from the app menu) opens the dashboard.
+pub fn handle_second_instance(app: &AppHandle, argv: Vec<String>) {
+    let args = LaunchArgs::parse(argv);
+    if args.is_empty() {
+        show_dashboard(app);
+        return;
+    }
+    handle_args(app, args);
+}
+
+pub fn handle_args(app: &AppHandle, args: LaunchArgs) {
+    if args.show {
+        show_dashboard(app);
+    }
+
+    for server in args.access {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let _ = request_access_and_notify(app, server).await;
+        });
+    }
+}
+
+/// Requests access outside the dashboard's own flow and tells the dashboard
+/// about the outcome, so it can show an error on the server card.
+pub async fn request_access_and_notify<R: Runtime>(app: AppHandle<R>, server: String) -> Result<(), String> {
+    let result = ServerService::request_access(&app, &server).await;
+    if let Err(e) = &result {
+        eprintln!("[Access] Request for '{}' failed: {}", server, e);
+    }
+    let _ = app.emit("access-requested", AccessRequested { server, error: result.clone().err() });
+    result
+}
@@ -1,2 +1,5 @@
+pub mod deep_link;
+pub mod instance;
+pub mod shortcut;
 pub mod tray;
 pub mod window;
\ No newline at end of file
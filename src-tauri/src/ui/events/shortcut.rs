@@ -0,0 +1,66 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use crate::core::settings::Settings;
+use crate::state::AppState;
+use crate::ui::events::instance::request_access_and_notify;
+use crate::ui::events::tray::toggle_dashboard;
+
+/// Emitted with the full list of failures each time the shortcuts are applied.
+pub const SHORTCUT_FAILURES_EVENT: &str = "shortcut-failures";
+
+/// A shortcut that could not be registered, usually because another app owns it.
+#[derive(Debug, Serialize, Clone)]
+pub struct ShortcutFailure {
+    pub shortcut: String,
+    pub error: String,
+}
+
+/// Registers the shortcuts from the settings, replacing whatever was registered
+/// before. A shortcut another app already owns is skipped and reported to the
+/// settings page, so one conflict does not take the others down with it.
+pub fn apply<R: Runtime>(app: &AppHandle<R>, settings: &Settings) {
+    let shortcuts = app.global_shortcut();
+    if let Err(e) = shortcuts.unregister_all() {
+        eprintln!("[Shortcuts] Failed to unregister: {}", e);
+    }
+
+    let mut failures = Vec::new();
+    let mut record = |shortcut: &str, e: tauri_plugin_global_shortcut::Error| {
+        eprintln!("[Shortcuts] Could not register '{}': {}", shortcut, e);
+        failures.push(ShortcutFailure { shortcut: shortcut.to_string(), error: e.to_string() });
+    };
+
+    if let Some(shortcut) = settings.dashboard_shortcut.as_deref() {
+        let result = shortcuts.on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                toggle_dashboard(app);
+            }
+        });
+        if let Err(e) = result {
+            record(shortcut, e);
+        }
+    }
+
+    for favourite in &settings.server_shortcuts {
+        let server = favourite.server.clone();
+        let result = shortcuts.on_shortcut(favourite.shortcut.as_str(), move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                request_access(app, server.clone());
+            }
+        });
+        if let Err(e) = result {
+            record(&favourite.shortcut, e);
+        }
+    }
+
+    *app.state::<AppState>().shortcut_failures.lock().unwrap() = failures.clone();
+    let _ = app.emit(SHORTCUT_FAILURES_EVENT, failures);
+}
+
+fn request_access<R: Runtime>(app: &AppHandle<R>, server: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = request_access_and_notify(app, server).await;
+    });
+}
@@ -1,11 +1,18 @@
 use tauri::{
     tray::{MouseButton, MouseButtonState, TrayIconEvent},
-    AppHandle, Manager, Runtime, WebviewWindow,
+    AppHandle, Emitter, Manager, Runtime, WebviewWindow,
 };
+use tauri_plugin_opener::OpenerExt;
 use tauri_plugin_positioner::{Position, WindowExt};
 use std::sync::atomic::Ordering;
 use crate::AppState;
+use crate::api::auth::logout;
+use crate::api::extensions::{run_extension, stop_extension};
 use crate::constants::MAIN_WINDOW_LABEL;
+use crate::core::servers::ServerService;
+use crate::core::settings::SettingsService;
+use crate::core::notifications::NotificationService;
+use crate::ui::tray::{EXTEND_ACCESS_PREFIX, EXTENSION_TOGGLE_PREFIX, GROUP_ACCESS_PREFIX, SERVER_ACCESS_PREFIX};
 
 // --- Helper: Position & Show ---
 fn position_and_show<R: Runtime>(window: &WebviewWindow<R>) -> tauri::Result<()> {
@@ -27,6 +34,19 @@ fn position_and_show<R: Runtime>(window: &WebviewWindow<R>) -> tauri::Result<()>
     Ok(())
 }
 
+/// Brings up the dashboard next to the tray icon.
+pub fn show_dashboard<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        let _ = position_and_show(&window);
+    }
+}
+
+/// Shows the dashboard on a given route, e.g. `/settings`.
+pub fn open_route<R: Runtime>(app: &AppHandle<R>, route: &str) {
+    show_dashboard(app);
+    let _ = app.emit("navigate", route);
+}
+
 // --- Handler: Left Click on Icon ---
 pub fn handle_icon_click<R: Runtime>(app: &AppHandle<R>, event: TrayIconEvent) {
     // We only care about the specific Left Click UP event
@@ -36,13 +56,17 @@ pub fn handle_icon_click<R: Runtime>(app: &AppHandle<R>, event: TrayIconEvent) {
         ..
     } = event
     {
-        if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
-            // Toggle Logic
-            if window.is_visible().unwrap_or(false) {
-                let _ = window.hide();
-            } else {
-                let _ = position_and_show(&window);
-            }
+        toggle_dashboard(app);
+    }
+}
+
+/// Hides the dashboard if it is showing, otherwise brings it up next to the tray.
+pub fn toggle_dashboard<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
+        if window.is_visible().unwrap_or(false) {
+            let _ = window.hide();
+        } else {
+            let _ = position_and_show(&window);
         }
     }
 }
@@ -56,11 +80,91 @@ pub fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, event_id: &str) {
             state.is_quitting.store(true, Ordering::Relaxed);
             app.exit(0);
         }
-        "show" => {
-            if let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) {
-                let _ = position_and_show(&window);
+        "show" => show_dashboard(app),
+        "settings" => open_route(app, "/settings"),
+        "login" => open_route(app, "/login"),
+        "logout" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = logout(app.clone()).await {
+                    eprintln!("[Tray] Logout failed: {}", e);
+                }
+                // Same as the dashboard's logout; the settings change tells the UI.
+                let _ = SettingsService::modify(&app, |settings| {
+                    settings.username = None;
+                    Ok(())
+                });
+            });
+        }
+        "device_login" => {
+            let state = app.state::<AppState>();
+            let url = state.device_login.lock().unwrap().as_ref().map(|device| {
+                device.verification_uri_complete.clone()
+                    .unwrap_or_else(|| device.verification_uri.clone())
+            });
+            if let Some(url) = url {
+                let _ = app.opener().open_url(url, None::<&str>);
+            }
+        }
+        id => {
+            let server = id.strip_prefix(SERVER_ACCESS_PREFIX).or_else(|| id.strip_prefix(EXTEND_ACCESS_PREFIX));
+            if let Some(server) = server {
+                let app = app.clone();
+                let server = server.to_string();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = ServerService::request_access(&app, &server).await {
+                        eprintln!("[Tray] Access request for '{}' failed: {}", server, e);
+                    }
+                });
+            } else if let Some(group) = id.strip_prefix(GROUP_ACCESS_PREFIX) {
+                request_group_access(app, group.to_string());
+            } else if let Some(extension) = id.strip_prefix(EXTENSION_TOGGLE_PREFIX) {
+                toggle_extension(app, extension.to_string());
             }
         }
-        _ => {}
     }
+}
+
+/// The tray has no result view, so the outcome is summarised in a notification.
+fn request_group_access<R: Runtime>(app: &AppHandle<R>, group: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let body = match ServerService::request_group_access(&app, &group).await {
+            Ok(results) => {
+                let failed: Vec<&str> = results.iter()
+                    .filter(|r| r.error.is_some())
+                    .map(|r| r.server.as_str())
+                    .collect();
+                match failed.len() {
+                    0 => format!("Access granted to all {} servers.", results.len()),
+                    n => format!(
+                        "Access granted to {} of {} servers. Failed: {}",
+                        results.len() - n,
+                        results.len(),
+                        failed.join(", ")
+                    ),
+                }
+            }
+            Err(e) => e,
+        };
+        NotificationService::notify(&app, &format!("Group '{}'", group), &body);
+    });
+}
+
+fn toggle_extension<R: Runtime>(app: &AppHandle<R>, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let running = state.running_extensions.lock().unwrap().contains_key(&id);
+        let result = if running {
+            stop_extension(app.clone(), state, id.clone()).await
+        } else {
+            run_extension(app.clone(), state, id.clone()).await
+        };
+        if let Err(e) = result {
+            eprintln!("[Tray] Failed to toggle extension '{}': {}", id, e);
+            // The check mark already flipped; put it back.
+            let _ = app.emit("extensions-changed", ());
+        }
+    });
 }
\ No newline at end of file
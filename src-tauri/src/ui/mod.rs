@@ -1,4 +1,5 @@
 pub mod definitions;
+pub mod dialogs;
 pub mod events;
 pub mod tray;
 pub mod windows;
\ No newline at end of file
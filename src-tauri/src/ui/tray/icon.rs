@@ -0,0 +1,86 @@
+use tauri::image::Image;
+
+/// What the tray icon shows, most important first: a state further up hides
+/// the ones below it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrayState {
+    LoggedOut,
+    BackendOffline,
+    ExtensionCrashed,
+    AccessActive,
+    Idle,
+}
+
+#[derive(Clone, Copy)]
+enum Badge {
+    Dot,
+    Ring,
+    Square,
+}
+
+const GREEN: [u8; 3] = [0x10, 0xb9, 0x81];
+const RED: [u8; 3] = [0xef, 0x44, 0x44];
+const AMBER: [u8; 3] = [0xf5, 0x9e, 0x0b];
+const BLACK: [u8; 3] = [0, 0, 0];
+
+/// Draws the state onto the app icon. Template icons (macOS) are drawn in
+/// black only, since the menu bar recolours them; the badge shape tells the
+/// states apart there.
+pub fn render(base: &Image<'_>, state: TrayState, template: bool) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+
+    let badge = match state {
+        TrayState::Idle => None,
+        TrayState::LoggedOut => {
+            fade(&mut rgba);
+            None
+        }
+        TrayState::AccessActive => Some((Badge::Dot, GREEN)),
+        TrayState::BackendOffline => Some((Badge::Ring, RED)),
+        TrayState::ExtensionCrashed => Some((Badge::Square, AMBER)),
+    };
+
+    if let Some((shape, color)) = badge {
+        draw_badge(&mut rgba, width, height, shape, if template { BLACK } else { color });
+    }
+    Image::new_owned(rgba, width, height)
+}
+
+fn fade(rgba: &mut [u8]) {
+    for pixel in rgba.chunks_exact_mut(4) {
+        pixel[3] = (pixel[3] as u16 * 2 / 5) as u8;
+    }
+}
+
+/// A badge in the bottom-right corner, with a transparent gap around it so it
+/// stays readable on top of the icon.
+fn draw_badge(rgba: &mut [u8], width: u32, height: u32, shape: Badge, color: [u8; 3]) {
+    let radius = (width.min(height) as f32 / 4.0).max(2.0);
+    let gap = (radius / 4.0).max(1.0);
+    let (cx, cy) = (width as f32 - radius - 0.5, height as f32 - radius - 0.5);
+
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+            let distance = (dx * dx + dy * dy).sqrt();
+            if distance > radius + gap {
+                continue;
+            }
+
+            let inside = match shape {
+                Badge::Dot => distance <= radius,
+                Badge::Ring => distance <= radius && distance >= radius * 0.55,
+                Badge::Square => dx.abs() <= radius * 0.75 && dy.abs() <= radius * 0.75,
+            };
+
+            let i = ((y * width + x) * 4) as usize;
+            if inside {
+                rgba[i..i + 3].copy_from_slice(&color);
+                rgba[i + 3] = 0xff;
+            } else {
+                rgba[i + 3] = 0;
+            }
+        }
+    }
+}
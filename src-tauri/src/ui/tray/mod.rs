@@ -1,26 +1,119 @@
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu},
     tray::TrayIconBuilder,
-    AppHandle, Runtime,
+    AppHandle, Listener, Manager, Runtime,
 };
+use std::sync::Mutex;
+use crate::api::extensions::{get_base_name, installed_extensions};
+use crate::constants::TRAY_ID;
+use crate::core::keychain::KeychainService;
+use crate::core::notifications::EXPIRY_WARNING_EVENT;
+use crate::core::profiles::ProfileService;
+use crate::core::servers::{ServerEntry, ServerService, ServerStatus};
+use crate::core::settings::SettingsService;
+use crate::state::AppState;
 use crate::ui::events;
+use icon::TrayState;
+
+mod icon;
+
+const DEFAULT_TOOLTIP: &str = "ServeMe";
+
+/// Menu item id prefixes; the rest of the id is the server or extension id.
+pub const SERVER_ACCESS_PREFIX: &str = "server_access:";
+pub const EXTENSION_TOGGLE_PREFIX: &str = "extension_toggle:";
+pub const GROUP_ACCESS_PREFIX: &str = "group_access:";
+/// Same action as `SERVER_ACCESS_PREFIX`, for the entries of servers about to expire.
+pub const EXTEND_ACCESS_PREFIX: &str = "extend_access:";
+
+/// Events after which the menu and icon are out of date.
+const REFRESH_EVENTS: [&str; 7] = [
+    "servers-changed",
+    "server-status-changed",
+    EXPIRY_WARNING_EVENT,
+    "settings-changed",
+    "session-changed",
+    "extensions-changed",
+    "extension-crash",
+];
+
+/// Events that only move server state along; the slow inputs stay as they are.
+const STATUS_EVENTS: [&str; 2] = ["server-status-changed", EXPIRY_WARNING_EVENT];
+
+/// Prefix of the per-server submenus, so their titles can be updated in place.
+const SERVER_MENU_PREFIX: &str = "server_menu:";
+
+/// What the menu shows. Rebuilding the menu closes it on some platforms (Linux),
+/// so on status polls it is only rebuilt when the layout changes; new server
+/// titles are set on the existing items.
+#[derive(Clone, PartialEq)]
+struct MenuModel {
+    device_code: Option<String>,
+    session: SessionInputs,
+    expiring: Vec<String>,
+    servers: Vec<ServerItem>,
+    groups: Vec<String>,
+}
+
+/// The inputs that are slow to read (keychain, settings file, extensions
+/// directory). Reloaded on the events that can change them, not on status polls.
+#[derive(Clone, PartialEq)]
+struct SessionInputs {
+    logged_in: bool,
+    username: Option<String>,
+    /// `None` while extensions are disabled.
+    extensions: Option<Vec<ExtensionItem>>,
+}
+
+#[derive(Clone, PartialEq)]
+struct ExtensionItem {
+    id: String,
+    label: String,
+    running: bool,
+}
+
+#[derive(Clone, PartialEq)]
+struct ServerItem {
+    id: String,
+    title: String,
+    action: &'static str,
+    enabled: bool,
+}
+
+impl MenuModel {
+    /// The same items in the same places; only server titles and actions differ.
+    fn same_layout(&self, other: &Self) -> bool {
+        let ids = |model: &Self| model.servers.iter().map(|s| s.id.clone()).collect::<Vec<_>>();
+        self.device_code == other.device_code
+            && self.session == other.session
+            && self.expiring == other.expiring
+            && self.groups == other.groups
+            && ids(self) == ids(other)
+    }
+}
+
+/// The menu in the tray and what it was built from.
+struct TrayMenu<R: Runtime> {
+    session: Mutex<Option<SessionInputs>>,
+    shown: Mutex<Option<(MenuModel, Menu<R>)>>,
+    icon: Mutex<Option<(TrayState, String)>>,
+}
 
 pub fn setup<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    app.manage(TrayMenu::<R> {
+        session: Mutex::new(None),
+        shown: Mutex::new(None),
+        icon: Mutex::new(None),
+    });
+
     // 1. Get the App Icon
     // Ensure you have icons in src-tauri/icons/ or this unwrap might panic in dev
     let icon = app.default_window_icon().expect("No app icon found").clone();
 
-    // 2. Define Menu Items
-    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-    let show_i = MenuItem::with_id(app, "show", "Open Dashboard", true, None::<&str>)?;
-
-    // 3. Build Menu
-    let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
-
-    // 4. Build Tray
-    let mut builder = TrayIconBuilder::new()
+    // 2. Build Tray; the menu is set by the first refresh below
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
         .icon(icon)
-        .menu(&menu)
+        .tooltip(DEFAULT_TOOLTIP)
         .show_menu_on_left_click(false);
 
     #[cfg(target_os = "macos")]
@@ -40,5 +133,287 @@ pub fn setup<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
         })
         .build(app)?;
 
+    for event in REFRESH_EVENTS {
+        let handle = app.clone();
+        let reload = !STATUS_EVENTS.contains(&event);
+        app.listen(event, move |_| {
+            // Some of these are emitted with state locks held; refresh once they are released.
+            let handle = handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = if reload { refresh(&handle) } else { update(&handle) };
+                if let Err(e) = result {
+                    eprintln!("[Tray] Failed to refresh: {}", e);
+                }
+            });
+        });
+    }
+
+    refresh(app)
+}
+
+/// Re-reads login state, settings and extensions and rebuilds the menu. The
+/// rebuild also resets check marks the menu flipped for a failed toggle.
+pub fn refresh<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let Some(tray_menu) = app.try_state::<TrayMenu<R>>() else {
+        return Ok(());
+    };
+    *tray_menu.session.lock().unwrap() = Some(load_session(app));
+    *tray_menu.shown.lock().unwrap() = None;
+    update(app)
+}
+
+/// Brings the menu, icon and tooltip in line with the current state, touching
+/// only what changed.
+fn update<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let Some(tray_menu) = app.try_state::<TrayMenu<R>>() else {
+        return Ok(());
+    };
+    let Some(session) = tray_menu.session.lock().unwrap().clone() else {
+        return Ok(());
+    };
+
+    let model = current_model(app, session);
+    let (tray_state, summary) = current_state(app, &model.session);
+    let tooltip = match app.state::<AppState>().device_login.lock().unwrap().as_ref() {
+        Some(device) => format!("Enter code {} at {}", device.user_code, device.verification_uri),
+        None => summary,
+    };
+
+    let mut shown_icon = tray_menu.icon.lock().unwrap();
+    if shown_icon.as_ref().map(|(state, _)| *state) != Some(tray_state) {
+        if let Some(base) = app.default_window_icon() {
+            tray.set_icon(Some(icon::render(base, tray_state, cfg!(target_os = "macos"))))?;
+        }
+    }
+    if shown_icon.as_ref().map(|(_, shown)| shown) != Some(&tooltip) {
+        tray.set_tooltip(Some(&tooltip))?;
+    }
+    *shown_icon = Some((tray_state, tooltip));
+    drop(shown_icon);
+
+    let mut shown = tray_menu.shown.lock().unwrap();
+    if shown.as_ref().is_some_and(|(previous, _)| *previous == model) {
+        return Ok(());
+    }
+    let same_layout = shown.as_ref()
+        .filter(|(previous, _)| previous.same_layout(&model))
+        .map(|(_, menu)| menu.clone());
+    let menu = match same_layout {
+        Some(menu) => {
+            update_servers(&menu, &model.servers)?;
+            menu
+        }
+        None => {
+            let menu = build_menu(app, &model)?;
+            tray.set_menu(Some(menu.clone()))?;
+            menu
+        }
+    };
+    *shown = Some((model, menu));
     Ok(())
-}
\ No newline at end of file
+}
+
+fn load_session<R: Runtime>(app: &AppHandle<R>) -> SessionInputs {
+    let state = app.state::<AppState>();
+    let settings = SettingsService::load(app).ok();
+    let extensions = settings.as_ref().filter(|s| s.extensions_enabled).map(|_| {
+        installed_extensions(app, &state)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|extension| ExtensionItem {
+                label: format!("{} {}", extension.name, extension.version),
+                id: extension.id,
+                running: extension.is_running,
+            })
+            .collect()
+    });
+
+    SessionInputs {
+        logged_in: KeychainService::get_session(&ProfileService::active_name(app)).is_ok(),
+        username: settings.and_then(|s| s.username),
+        extensions,
+    }
+}
+
+fn current_model<R: Runtime>(app: &AppHandle<R>, session: SessionInputs) -> MenuModel {
+    let state = app.state::<AppState>();
+    let device_code = state.device_login.lock().unwrap().as_ref().map(|device| device.user_code.clone());
+    let logged_in = session.logged_in;
+
+    MenuModel {
+        device_code,
+        expiring: if logged_in { state.expiry_notices.expiring() } else { Vec::new() },
+        servers: if logged_in { state.servers.list().iter().map(server_item).collect() } else { Vec::new() },
+        groups: if logged_in { ServerService::groups(app) } else { Vec::new() },
+        session,
+    }
+}
+
+/// The icon state and a one-line summary for the tooltip.
+fn current_state<R: Runtime>(app: &AppHandle<R>, session: &SessionInputs) -> (TrayState, String) {
+    let state = app.state::<AppState>();
+    if !session.logged_in {
+        return (TrayState::LoggedOut, format!("{}: not signed in", DEFAULT_TOOLTIP));
+    }
+
+    let servers = state.servers.list();
+    if servers.iter().any(|s| s.status == ServerStatus::Offline) {
+        return (TrayState::BackendOffline, format!("{}: backend unreachable", DEFAULT_TOOLTIP));
+    }
+
+    let crashed: Vec<String> = state.crashed_extensions.lock().unwrap().iter().cloned().collect();
+    if let Some(id) = crashed.first() {
+        let name = get_base_name(id);
+        return (TrayState::ExtensionCrashed, format!("{}: extension '{}' crashed", DEFAULT_TOOLTIP, name));
+    }
+
+    let active: Vec<&ServerEntry> = servers.iter().filter(|s| s.status == ServerStatus::AccessGranted).collect();
+    if active.is_empty() {
+        return (TrayState::Idle, format!("{}: no active access", DEFAULT_TOOLTIP));
+    }
+
+    let count = match active.len() {
+        1 => "1 server active".to_string(),
+        n => format!("{} servers active", n),
+    };
+    let summary = match active.iter().filter_map(|s| s.minutes_left()).min() {
+        Some(minutes) => format!("{}, next expiry in {}", count, format_duration(minutes)),
+        None => count,
+    };
+    (TrayState::AccessActive, summary)
+}
+
+fn build_menu<R: Runtime>(app: &AppHandle<R>, model: &MenuModel) -> tauri::Result<Menu<R>> {
+    let menu = Menu::new(app)?;
+
+    // Pending device login: show the code so headless-ish setups can finish it from the tray.
+    if let Some(code) = &model.device_code {
+        let code_i = MenuItem::with_id(app, "device_code", format!("Login code: {}", code), false, None::<&str>)?;
+        let open_i = MenuItem::with_id(app, "device_login", "Open Login Page", true, None::<&str>)?;
+        menu.append(&code_i)?;
+        menu.append(&open_i)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+
+    // Servers about to expire, where desktop notifications cannot offer the action.
+    if !model.expiring.is_empty() {
+        for server in &model.expiring {
+            let id = format!("{}{}", EXTEND_ACCESS_PREFIX, server);
+            menu.append(&MenuItem::with_id(app, id, format!("Extend Access to '{}'", server), true, None::<&str>)?)?;
+        }
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+
+    // Servers
+    if !model.servers.is_empty() {
+        for server in &model.servers {
+            menu.append(&server_menu(app, server)?)?;
+        }
+
+        if !model.groups.is_empty() {
+            let groups_m = Submenu::with_id(app, "groups", "Request Access to Group", true)?;
+            for group in &model.groups {
+                let id = format!("{}{}", GROUP_ACCESS_PREFIX, group);
+                groups_m.append(&MenuItem::with_id(app, id, group, true, None::<&str>)?)?;
+            }
+            menu.append(&groups_m)?;
+        }
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+
+    // Extensions
+    if let Some(extensions) = &model.session.extensions {
+        let extensions_m = Submenu::with_id(app, "extensions", "Extensions", true)?;
+        if extensions.is_empty() {
+            extensions_m.append(&MenuItem::with_id(app, "extensions_empty", "No extensions installed", false, None::<&str>)?)?;
+        }
+        for extension in extensions {
+            let id = format!("{}{}", EXTENSION_TOGGLE_PREFIX, extension.id);
+            extensions_m.append(&CheckMenuItem::with_id(app, id, &extension.label, true, extension.running, None::<&str>)?)?;
+        }
+        menu.append(&extensions_m)?;
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+
+    // Session
+    if model.session.logged_in {
+        let user = model.session.username.as_deref().unwrap_or("unknown user");
+        menu.append(&MenuItem::with_id(app, "session", format!("Signed in as {}", user), false, None::<&str>)?)?;
+        menu.append(&MenuItem::with_id(app, "logout", "Log Out", true, None::<&str>)?)?;
+    } else {
+        menu.append(&MenuItem::with_id(app, "session", "Not signed in", false, None::<&str>)?)?;
+        menu.append(&MenuItem::with_id(app, "login", "Log In...", true, None::<&str>)?)?;
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    let show_i = MenuItem::with_id(app, "show", "Open Dashboard", true, None::<&str>)?;
+    let settings_i = MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
+    let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    menu.append(&show_i)?;
+    menu.append(&settings_i)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&quit_i)?;
+
+    Ok(menu)
+}
+
+/// Titles and actions of the server submenus, for a menu whose layout is unchanged.
+fn update_servers<R: Runtime>(menu: &Menu<R>, servers: &[ServerItem]) -> tauri::Result<()> {
+    for server in servers {
+        let menu_id = format!("{}{}", SERVER_MENU_PREFIX, server.id);
+        let Some(MenuItemKind::Submenu(server_m)) = menu.get(menu_id.as_str()) else {
+            continue;
+        };
+        server_m.set_text(&server.title)?;
+        let access_id = format!("{}{}", SERVER_ACCESS_PREFIX, server.id);
+        if let Some(MenuItemKind::MenuItem(access_i)) = server_m.get(access_id.as_str()) {
+            access_i.set_text(server.action)?;
+            access_i.set_enabled(server.enabled)?;
+        }
+    }
+    Ok(())
+}
+
+fn server_item(server: &ServerEntry) -> ServerItem {
+    let status = match server.status {
+        ServerStatus::AccessGranted => match server.minutes_left() {
+            Some(minutes) => format!("active, {} left", format_duration(minutes)),
+            None => "active".to_string(),
+        },
+        ServerStatus::Idle => "no access".to_string(),
+        ServerStatus::Offline => "offline".to_string(),
+    };
+    ServerItem {
+        id: server.id.clone(),
+        title: format!("{} ({})", server.id, status),
+        action: match server.status {
+            ServerStatus::AccessGranted => "Extend Access",
+            _ => "Request Access",
+        },
+        enabled: server.status != ServerStatus::Offline,
+    }
+}
+
+/// One submenu per server: its status in the title, the access request inside.
+fn server_menu<R: Runtime>(app: &AppHandle<R>, server: &ServerItem) -> tauri::Result<Submenu<R>> {
+    let server_m = Submenu::with_id(app, format!("{}{}", SERVER_MENU_PREFIX, server.id), &server.title, true)?;
+    let access_i = MenuItem::with_id(
+        app,
+        format!("{}{}", SERVER_ACCESS_PREFIX, server.id),
+        server.action,
+        server.enabled,
+        None::<&str>,
+    )?;
+    server_m.append(&access_i)?;
+    Ok(server_m)
+}
+
+fn format_duration(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m.max(0)),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
@@ -1,109 +1,57 @@
-import {get} from 'svelte/store';
-import {fetch} from '@tauri-apps/plugin-http';
-import {authSession} from '$lib/stores/auth';
-import {serverUrl} from "$lib/stores/settings";
+import {invoke, type InvokeArgs} from '@tauri-apps/api/core';
 
 // ---------------------------------------------------------
-// CONFIGURATION
-// Change this to '/v1' when the backend updates.
-// Currently empty for unversioned API.
+// Backend calls go through the Rust client (core::backend), which owns the
+// base URL, the API prefix, timeouts and the mapping of status codes to
+// ERR_* codes. Rust rejects with the bare code string; it is wrapped in an
+// Error here so callers can keep matching on err.message.
 // ---------------------------------------------------------
-const API_PREFIX = '/api/v1';
 
-class ApiService {
-    /**
-     * Helper to construct headers and full URL dynamically
-     */
-    private async prepareRequest(endpoint: string) {
-        const session = get(authSession);
-        const baseUrl = get(serverUrl);
-
-        if (!baseUrl) {
-            throw new Error("ERR_OFFLINE");
-        }
-
-        const cleanBase = baseUrl.replace(/\/$/, "");
-
-        const url = `${cleanBase}${API_PREFIX}${endpoint}`;
-
-        const headers = {
-            'Authorization': session?.access_token ? `Bearer ${session.access_token}` : '',
-            'Content-Type': 'application/json'
-        };
-
-        return {url, headers};
-    }
+export interface AccessStatusResponse {
+    server: string;
+    ip: string | null;
+    is_active: boolean;
+    expiration: number | null;
+    time_remaining: string | null;
+}
 
-    private handleResponse(response: Response) {
-        if (response.ok) return response;
+export interface LoginResponse {
+    access: string;
+    refresh: string;
+}
 
-        switch (response.status) {
-            case 401:
-                throw new Error("ERR_AUTH");
-            case 403:
-                throw new Error("ERR_FORBIDDEN");
-            case 404:
-                throw new Error("ERR_NOT_FOUND");
-            case 429:
-                throw new Error("ERR_RATE_LIMIT");
-            case 500:
-                throw new Error("ERR_SERVER");
-            case 503:
-                throw new Error("ERR_OFFLINE");
-            default:
-                throw new Error(`ERR_UNKNOWN:${response.status}`);
+class ApiService {
+    private async call<T>(command: string, args?: InvokeArgs): Promise<T> {
+        try {
+            return await invoke<T>(command, args);
+        } catch (err: any) {
+            throw new Error(typeof err === 'string' ? err : err?.message ?? 'ERR_UNKNOWN');
         }
     }
 
-    async checkConnection(baseUrl: string): Promise<boolean> {
-        const cleanBase = baseUrl.replace(/\/$/, "");
-        const url = `${cleanBase}${API_PREFIX}/health`;
-
-        const controller = new AbortController();
-        const timeoutId = setTimeout(() => controller.abort(), 5000);
+    async checkConnection(baseUrl: string): Promise<void> {
+        await this.call('check_connection', {url: baseUrl});
+    }
 
-        try {
-            const response = await fetch(url, {
-                method: 'GET',
-                signal: controller.signal
-            });
-            clearTimeout(timeoutId);
-            return response.ok;
-        } catch (err) {
-            clearTimeout(timeoutId);
-            throw err;
-        }
+    login(username: string, password: string): Promise<LoginResponse> {
+        return this.call<LoginResponse>('login_password', {username, password});
     }
 
-    async get<T>(endpoint: string): Promise<T> {
-        const {url, headers} = await this.prepareRequest(endpoint);
+    logout(): Promise<void> {
+        return this.call('logout');
+    }
 
-        try {
-            const response = await fetch(url, {method: 'GET', headers});
-            this.handleResponse(response);
-            return await response.json() as T;
-        } catch (err: any) {
-            if (err.message && err.message.startsWith('ERR_')) throw err;
-            throw new Error("ERR_NETWORK");
-        }
+    getAccessStatus(server: string): Promise<AccessStatusResponse> {
+        return this.call<AccessStatusResponse>('get_access_status', {server});
     }
 
-    async post<T>(endpoint: string, body: object): Promise<T> {
-        const {url, headers} = await this.prepareRequest(endpoint);
+    serverExists(server: string): Promise<boolean> {
+        return this.call<boolean>('server_exists', {server});
+    }
 
-        try {
-            const response = await fetch(url, {
-                method: 'POST',
-                headers,
-                body: JSON.stringify(body)
-            });
-            this.handleResponse(response);
-            return await response.json().catch(() => ({})) as T;
-        } catch (err: any) {
-            if (err.message && err.message.startsWith('ERR_')) throw err;
-            throw new Error("ERR_NETWORK");
-        }
+    requestAccess(server: string): Promise<void> {
+        return this.call('request_access', {server});
     }
 }
 
-export const api = new ApiService();
\ No newline at end of file
+export const api = new ApiService();
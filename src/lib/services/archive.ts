@@ -0,0 +1,45 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type ConflictPolicy = 'keep' | 'replace';
+
+export interface ImportItem {
+    kind: 'setting' | 'profile' | 'server' | 'extension';
+    name: string;
+    action: 'add' | 'replace' | 'keep' | 'unchanged' | 'blocked';
+    reason: string | null;
+}
+
+export interface ImportReport {
+    applied: boolean;
+    items: ImportItem[];
+}
+
+export const ArchiveService = {
+    /**
+     * Asks for a destination and exports settings, servers, profiles and extensions.
+     * @returns The chosen path, or null if the dialog was cancelled.
+     */
+    async export(): Promise<string | null> {
+        return await invoke<string | null>('export_config');
+    },
+
+    /**
+     * Asks for an archive to import. Rust keeps the path; previews and the
+     * import itself always use the archive picked last.
+     * @returns The file name, or null if the dialog was cancelled.
+     */
+    async pick(): Promise<string | null> {
+        return await invoke<string | null>('pick_config_archive');
+    },
+
+    /**
+     * Compares the picked archive with the current configuration without changing anything.
+     */
+    async preview(onConflict: ConflictPolicy): Promise<ImportReport> {
+        return await invoke<ImportReport>('import_config', { dryRun: true, onConflict });
+    },
+
+    async import(onConflict: ConflictPolicy): Promise<ImportReport> {
+        return await invoke<ImportReport>('import_config', { dryRun: false, onConflict });
+    }
+};
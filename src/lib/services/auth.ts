@@ -1,6 +1,16 @@
 import {db} from '$lib/stores/app-db';
-import {api} from '$lib/services/api';
-import {isAuthenticated, authLoading, authError, authSession, type Session} from '$lib/stores/auth';
+import {api, type LoginResponse} from '$lib/services/api';
+import {
+    isAuthenticated,
+    authLoading,
+    authError,
+    authSession,
+    deviceLogin,
+    sessionInfo,
+    type DeviceLoginPrompt,
+    type SessionInfo,
+    type Session
+} from '$lib/stores/auth';
 import {mapBackendError} from "$lib/utils";
 import {get} from "svelte/store";
 import {invoke} from "@tauri-apps/api/core";
@@ -9,16 +19,15 @@ import {listen} from "@tauri-apps/api/event";
 const KEY_USERNAME = 'username';
 let isRefreshing = false;
 
-interface LoginResponse {
-    access_token: string;
-    refresh_token: string;
-}
-
 interface TokenPayload {
     access: string;
     refresh: string;
 }
 
+interface SsoLoginResult extends TokenPayload {
+    username: string;
+}
+
 export const AuthService = {
     /**
      * Initialize Auth: Load token from disk and check if valid.
@@ -33,14 +42,13 @@ export const AuthService = {
                 return;
             }
 
-            const exp = getJwtExpiration(session.access_token);
-            const now = Math.floor(Date.now() / 1000);
+            const info = await this.loadSessionInfo();
 
-            if (exp && exp > now) {
+            if (info && info.expiresIn > 0) {
                 authSession.set(session);
                 isAuthenticated.set(true);
             } else {
-                await refreshToken(session.username, session.refresh_token);
+                await refreshToken(session.username);
             }
         } catch (err: any) {
             console.error("[AuthService] Init Error:", err);
@@ -51,6 +59,31 @@ export const AuthService = {
         }
     },
 
+    /**
+     * Fetches the decoded claims of the current session from Rust.
+     * Returns null when there is no session or the token cannot be parsed.
+     */
+    async loadSessionInfo(): Promise<SessionInfo | null> {
+        try {
+            const info = await invoke<SessionInfo>('get_session_info');
+            sessionInfo.set(info);
+            return info;
+        } catch (err) {
+            sessionInfo.set(null);
+            return null;
+        }
+    },
+
+    /**
+     * Clears the in-memory session and re-runs init.
+     * Used after switching profiles, since every profile has its own keychain entry.
+     */
+    async reload() {
+        isAuthenticated.set(false);
+        authSession.set(null);
+        await this.init();
+    },
+
     async setupRefreshListener() {
         await listen<TokenPayload>("tokens-refreshed", (event) => {
             const current = get(authSession);
@@ -61,6 +94,7 @@ export const AuthService = {
                 access_token: event.payload.access,
                 refresh_token: event.payload.refresh
             });
+            AuthService.loadSessionInfo();
             console.log("[AuthService] Session synced with background refresh.");
         });
     },
@@ -73,14 +107,9 @@ export const AuthService = {
         authError.set("");
 
         try {
-            const data = await api.post<LoginResponse>('/login', {
-                username,
-                password: pass
-            });
-
-            if (data.access_token && data.refresh_token) {
-                await updateCredentials(data, username);
-            }
+            // Rust verifies the tokens and stores them in the keychain.
+            const data = await api.login(username, pass);
+            await updateCredentials(data, username);
 
         } catch (err: any) {
             console.error("[AuthService] Login failed:", err);
@@ -96,48 +125,73 @@ export const AuthService = {
         }
     },
 
+    /**
+     * Browser-based single sign-on. Rust opens the system browser, waits for the
+     * redirect and stores the tokens itself, so only the username is persisted here.
+     */
+    async loginWithSso() {
+        authLoading.set(true);
+        authError.set("");
+
+        try {
+            const result = await invoke<SsoLoginResult>('login_sso');
+            await applyIdpLogin(result);
+        } catch (err: any) {
+            console.error("[AuthService] SSO login failed:", err);
+            authError.set(`${err}`);
+        } finally {
+            authLoading.set(false);
+        }
+    },
+
+    /**
+     * Device authorization grant. Resolves once the code has been approved on
+     * another device; the code itself arrives through "device-login-pending".
+     */
+    async loginWithDevice() {
+        authLoading.set(true);
+        authError.set("");
+
+        const unlisten = await listen<DeviceLoginPrompt>("device-login-pending", (event) => {
+            deviceLogin.set(event.payload);
+        });
+
+        try {
+            const result = await invoke<SsoLoginResult>('login_device');
+            await applyIdpLogin(result);
+        } catch (err: any) {
+            console.error("[AuthService] Device login failed:", err);
+            authError.set(`${err}`);
+        } finally {
+            unlisten();
+            deviceLogin.set(null);
+            authLoading.set(false);
+        }
+    },
+
+    async cancelDeviceLogin() {
+        await invoke('cancel_device_login');
+    },
+
     /**
      * Logout Function
      */
     async logout() {
-        const session = get(authSession);
-
         try {
-            if (session?.refresh_token) {
-                await api.post('/logout', {refresh_token: session.refresh_token});
-            }
+            // Revokes the refresh token on the backend and purges the keychain entry.
+            await api.logout();
         } catch (err: any) {
-            console.error("[AuthService] Server-side logout failed:", err.message);
+            console.error("[AuthService] Logout failed:", err.message);
         } finally {
             isAuthenticated.set(false);
             authSession.set(null);
+            sessionInfo.set(null);
 
             await db.set(KEY_USERNAME, null);
-            await db.save();
-
-            await invoke('purge_tokens');
         }
     },
 }
 
-function getJwtExpiration(token: string | null): number | null {
-    if (!token) return null;
-    try {
-        const payloadBase64 = token.split('.')[1];
-        if (!payloadBase64) return null;
-
-        const base64 = payloadBase64.replace(/-/g, '+').replace(/_/g, '/');
-        const jsonPayload = decodeURIComponent(atob(base64).split('').map(c => {
-            return '%' + ('00' + c.charCodeAt(0).toString(16)).slice(-2);
-        }).join(''));
-
-        const parsed = JSON.parse(jsonPayload);
-        return parsed.exp || null;
-    } catch (e) {
-        return null;
-    }
-}
-
 async function getSession() {
     try {
         const username = await db.get<string>(KEY_USERNAME);
@@ -160,39 +214,54 @@ async function updateCredentials(data: LoginResponse, username: string | null) {
     if (!username) return;
 
     const session: Session = {
-        access_token: data.access_token,
-        refresh_token: data.refresh_token,
+        access_token: data.access,
+        refresh_token: data.refresh,
         username: username
     }
 
     await db.set(KEY_USERNAME, session.username);
-    await db.save();
-
-    await invoke('save_tokens', {
-        accessToken: data.access_token,
-        refreshToken: data.refresh_token
-    })
 
     authSession.set(session);
     isAuthenticated.set(true);
+    await AuthService.loadSessionInfo();
 }
 
-async function refreshToken(username: string | null, refresh_token: string | null) {
-    if (!username || !refresh_token || isRefreshing) return false;
+async function applyIdpLogin(result: SsoLoginResult) {
+    await db.set(KEY_USERNAME, result.username);
+
+    authSession.set({
+        access_token: result.access,
+        refresh_token: result.refresh,
+        username: result.username
+    });
+    isAuthenticated.set(true);
+    await AuthService.loadSessionInfo();
+}
+
+/**
+ * Refreshes through Rust, which knows whether the session came from the
+ * backend login or from SSO and stores the new tokens in the keychain.
+ */
+async function refreshToken(username: string | null) {
+    if (!username || isRefreshing) return false;
     isRefreshing = true;
 
     try {
-        const data = await api.post<LoginResponse>('/token/refresh', {
-            username,
-            refresh_token
-        });
+        const tokens = await invoke<TokenPayload>('refresh_session');
 
-        await updateCredentials(data, username);
+        authSession.set({
+            access_token: tokens.access,
+            refresh_token: tokens.refresh,
+            username: username
+        });
+        isAuthenticated.set(true);
+        await AuthService.loadSessionInfo();
         return true;
     } catch (err: any) {
         console.error("[AuthService] Refresh failed:", err);
+        const code = typeof err === 'string' ? err : err.message;
         // Only logout on explicit auth failure. Network errors should not log the user out.
-        if (err.message === 'ERR_AUTH' || err.message === 'ERR_FORBIDDEN') {
+        if (code === 'ERR_AUTH' || code === 'ERR_FORBIDDEN') {
             await AuthService.logout();
         }
         return false;
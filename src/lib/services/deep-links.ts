@@ -0,0 +1,48 @@
+import { ask, message } from '@tauri-apps/plugin-dialog';
+import { invoke } from '@tauri-apps/api/core';
+import { ServerService } from '$lib/services/servers';
+
+export interface PendingDeepLink {
+    id: number;
+    description: string;
+    action: 'access' | 'addServer' | 'runExtension' | 'updateSettings';
+}
+
+/**
+ * serveme:// links are parsed and validated in Rust; only the id of a queued
+ * action comes back here, after the user confirmed it.
+ */
+export const DeepLinkService = {
+    async pending(): Promise<PendingDeepLink[]> {
+        return await invoke<PendingDeepLink[]>('list_deep_links');
+    },
+
+    async prompt(link: PendingDeepLink): Promise<void> {
+        await invoke('set_dialog_status', { isOpen: true });
+        try {
+            const confirmed = await ask(link.description, { title: 'ServeMe', kind: 'warning' });
+            if (!confirmed) {
+                await invoke('dismiss_deep_link', { id: link.id });
+                return;
+            }
+
+            try {
+                await invoke('confirm_deep_link', { id: link.id });
+                if (link.action === 'addServer') await ServerService.load();
+            } catch (error) {
+                await message(`${error}`, { title: 'ServeMe', kind: 'error' });
+            }
+        } finally {
+            await invoke('set_dialog_status', { isOpen: false });
+        }
+    },
+
+    async reject(error: string): Promise<void> {
+        await invoke('set_dialog_status', { isOpen: true });
+        try {
+            await message(`This link cannot be opened: ${error}`, { title: 'ServeMe', kind: 'error' });
+        } finally {
+            await invoke('set_dialog_status', { isOpen: false });
+        }
+    }
+};
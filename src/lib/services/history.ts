@@ -0,0 +1,41 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export type HistoryEvent = 'requested' | 'granted' | 'renewed' | 'expired' | 'failed';
+export type ExportFormat = 'csv' | 'json';
+
+export interface HistoryRecord {
+    time: string;
+    server: string;
+    event: HistoryEvent;
+    ip: string | null;
+    expiration: number | null;
+    detail: string | null;
+}
+
+/** Dates are YYYY-MM-DD in local time, both inclusive. */
+export interface HistoryQuery {
+    server?: string | null;
+    from?: string | null;
+    to?: string | null;
+}
+
+/**
+ * The local access history kept by Rust (core::history) for compliance reports.
+ */
+export const HistoryService = {
+
+    /**
+     * Matching records, newest first
+     */
+    async query(query: HistoryQuery, limit?: number): Promise<HistoryRecord[]> {
+        return await invoke<HistoryRecord[]>('query_access_history', { query, limit });
+    },
+
+    /**
+     * Asks for a destination (the dialog is opened by Rust) and writes the matching records there.
+     * @returns The number of records written, or null if the dialog was cancelled.
+     */
+    async export(query: HistoryQuery, format: ExportFormat): Promise<number | null> {
+        return await invoke<number | null>('export_access_history', { format, query });
+    }
+};
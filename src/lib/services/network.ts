@@ -0,0 +1,51 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface TlsSettings {
+    caBundle: string | null;
+    clientCert: string | null;
+    pinnedSpki: string[];
+    hasClientKey: boolean;
+}
+
+export type ProxyMode = 'system' | 'none' | 'manual';
+
+export interface ProxySettings {
+    mode: ProxyMode;
+    url: string | null;
+    noProxy: string | null;
+    username: string | null;
+    hasPassword: boolean;
+}
+
+export const NetworkService = {
+    async getTls(): Promise<TlsSettings> {
+        return await invoke<TlsSettings>('get_tls_settings');
+    },
+
+    /**
+     * Rust builds a client from the new settings before saving them, so a bad
+     * bundle or a key that does not match the certificate is rejected here.
+     * @param clientKeyPath PEM key to import into the keychain, if it changed.
+     */
+    async updateTls(settings: TlsSettings, clientKeyPath: string | null = null): Promise<TlsSettings> {
+        return await invoke<TlsSettings>('update_tls_settings', { settings, clientKeyPath });
+    },
+
+    async getProxy(): Promise<ProxySettings> {
+        return await invoke<ProxySettings>('get_proxy_settings');
+    },
+
+    /**
+     * Credentials go to the keychain. Pass null as the password to keep the stored one.
+     */
+    async updateProxy(settings: ProxySettings, password: string | null = null): Promise<ProxySettings> {
+        return await invoke<ProxySettings>('update_proxy_settings', { settings, password });
+    },
+
+    /**
+     * Calls the backend health endpoint through the given settings without saving them.
+     */
+    async testProxy(settings: ProxySettings, password: string | null = null): Promise<void> {
+        await invoke('test_proxy_settings', { settings, password });
+    },
+};
@@ -0,0 +1,36 @@
+import { registerActionTypes, onAction } from '@tauri-apps/plugin-notification';
+import type { PluginListener } from '@tauri-apps/api/core';
+import { ServerService } from '$lib/services/servers';
+
+// Must match EXPIRY_ACTION_TYPE in core::notifications.
+const EXPIRY_ACTION_TYPE = 'access-expiry';
+
+export const NotificationService = {
+
+    /**
+     * Adds "Extend access" to the expiry notifications raised by Rust.
+     * Only platforms with notification actions show it; elsewhere the
+     * registration fails and the card's Extend button is the way to go.
+     */
+    async registerActions(): Promise<PluginListener | null> {
+        try {
+            await registerActionTypes([{
+                id: EXPIRY_ACTION_TYPE,
+                actions: [{ id: 'extend', title: 'Extend access' }]
+            }]);
+
+            return await onAction(async (notification) => {
+                const server = notification.extra?.server;
+                if (typeof server !== 'string') return;
+                try {
+                    await ServerService.requestAccess(server);
+                } catch (err) {
+                    console.error("[NotificationService] Extend failed:", err);
+                }
+            });
+        } catch (err) {
+            console.info("[NotificationService] Notification actions unavailable:", err);
+            return null;
+        }
+    }
+};
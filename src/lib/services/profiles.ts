@@ -0,0 +1,42 @@
+import { invoke } from '@tauri-apps/api/core';
+
+export interface Profile {
+    name: string;
+    serverUrl: string;
+    username: string | null;
+    oidcIssuer?: string | null;
+    oidcClientId?: string | null;
+}
+
+export const ProfileService = {
+    /**
+     * Lists all profiles. The active profile reflects the current settings.
+     */
+    async list(): Promise<Profile[]> {
+        return await invoke<Profile[]>('list_profiles');
+    },
+
+    async active(): Promise<Profile> {
+        return await invoke<Profile>('get_active_profile');
+    },
+
+    /**
+     * Creates a new profile without switching to it.
+     * @param name Letters, digits, '-' and '_' only.
+     */
+    async create(name: string, serverUrl: string, username: string | null = null): Promise<void> {
+        await invoke('create_profile', { profile: { name, serverUrl, username } });
+    },
+
+    /**
+     * Switches the active profile. The backend emits "profile-changed",
+     * which the layout uses to reload settings and the session.
+     */
+    async switch(name: string): Promise<Profile> {
+        return await invoke<Profile>('switch_profile', { name });
+    },
+
+    async delete(name: string): Promise<void> {
+        await invoke('delete_profile', { name });
+    }
+};
@@ -0,0 +1,70 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { AccessSchedule, ScheduleRule, ScheduleTarget } from '$lib/stores/app-db';
+import type { AccessResult } from '$lib/services/servers';
+
+export interface ScheduleEntry extends AccessSchedule {
+    nextRuns: string[];
+}
+
+export interface JobRecord {
+    time: string;
+    schedule: string;
+    target: ScheduleTarget;
+    scheduledFor: string;
+    outcome: 'succeeded' | 'partial' | 'failed' | 'missed';
+    results?: AccessResult[];
+    reason: string | null;
+}
+
+/**
+ * Scheduled access requests. Rust (core::scheduler) fires them while the app
+ * runs, whether or not the window is open; the UI only edits and previews them.
+ */
+export const ScheduleService = {
+
+    async list(): Promise<ScheduleEntry[]> {
+        return await invoke<ScheduleEntry[]>('list_schedules');
+    },
+
+    async add(target: ScheduleTarget, rule: ScheduleRule): Promise<ScheduleEntry[]> {
+        return await invoke<ScheduleEntry[]>('add_schedule', { target, rule });
+    },
+
+    async remove(id: string): Promise<ScheduleEntry[]> {
+        return await invoke<ScheduleEntry[]>('remove_schedule', { id });
+    },
+
+    async setEnabled(id: string, enabled: boolean): Promise<ScheduleEntry[]> {
+        return await invoke<ScheduleEntry[]>('set_schedule_enabled', { id, enabled });
+    },
+
+    /**
+     * Next runs of a rule before it is saved; rejects with the parse error
+     */
+    async preview(rule: ScheduleRule): Promise<string[]> {
+        return await invoke<string[]>('preview_schedule', { rule });
+    },
+
+    /**
+     * Executed (and missed) jobs, newest first
+     */
+    async log(limit = 20): Promise<JobRecord[]> {
+        return await invoke<JobRecord[]>('get_schedule_log', { limit });
+    },
+
+    /**
+     * Calls back whenever a scheduled job has run. Returns the unlisten function.
+     */
+    async onRun(callback: (record: JobRecord) => void): Promise<UnlistenFn> {
+        return await listen<JobRecord>('schedule-ran', (event) => callback(event.payload));
+    }
+};
+
+export function describeTarget(target: ScheduleTarget): string {
+    return 'server' in target ? target.server : `group ${target.group}`;
+}
+
+export function describeRule(rule: ScheduleRule): string {
+    return 'cron' in rule ? rule.cron : `once at ${new Date(rule.once).toLocaleString()}`;
+}
@@ -1,130 +1,114 @@
-import { get } from 'svelte/store';
-import { db } from '$lib/stores/app-db';
-import { api } from '$lib/services/api';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { servers, type ServerCard } from '$lib/stores/servers';
 
-const KEY_SAVED_SERVERS = 'saved_servers';
+export interface AccessResult {
+    server: string;
+    error: string | null;
+}
 
-// Private API Response Type
-interface AccessStatusResponse {
+export interface ServerInfo {
+    id: string;
+    description: string | null;
+    environment: string | null;
+    tags: string[];
+}
+
+export interface RenewalRecord {
+    time: string;
     server: string;
-    ip: string;
-    is_active: boolean;
-    expiration: number | null;
-    time_remaining: string | null;
+    outcome: 'renewed' | 'failed' | 'skipped';
+    reason: string | null;
 }
 
+/**
+ * The server list and its access state are owned by Rust (core::servers),
+ * which keeps polling while the window is hidden. The UI only mirrors it.
+ */
 export const ServerService = {
 
     /**
-     * Loads saved servers from disk into the store
+     * Loads the registry into the store
      */
     async load() {
         try {
-            const stored = await db.get<ServerCard[]>(KEY_SAVED_SERVERS);
-            if (stored) {
-                // Reset status on load so we don't show stale "online" states
-                const safeList = stored.map(s => ({
-                    ...s,
-                    status: 'idle' as const,
-                    timeRemaining: null
-                }));
-                servers.set(safeList);
-            }
+            servers.set(await invoke<ServerCard[]>('list_servers'));
         } catch (err) {
             console.error("[ServerService] Load failed:", err);
         }
     },
 
     /**
-     * Checks the access status for a single server
+     * Keeps the store in sync with the registry. Returns the unlisten function.
      */
-    async checkStatus(serverName: string) {
-        try {
-            const data = await api.get<AccessStatusResponse>(`/users/access/${serverName}/status`);
-
-            updateServerState(serverName, {
-                status: data.is_active ? 'access-granted' : 'idle',
-                timeRemaining: data.time_remaining
-            });
-
-        } catch (err: any) {
-            const msg = err.message || "";
+    async listen(): Promise<UnlistenFn> {
+        const unlistenList = await listen<ServerCard[]>('servers-changed', (event) => {
+            servers.set(event.payload);
+        });
+        const unlistenStatus = await listen<ServerCard>('server-status-changed', (event) => {
+            const entry = event.payload;
+            servers.update(list => list.map(s => s.id === entry.id ? entry : s));
+        });
 
-            if (msg === "ERR_AUTH") {
-                console.warn("Token expired during polling");
-                updateServerState(serverName, { status: 'idle' });
-            } else if (msg === "ERR_NETWORK" || msg === "ERR_OFFLINE") {
-                updateServerState(serverName, { status: 'offline' });
-            } else {
-                updateServerState(serverName, { status: 'idle' });
-            }
-        }
+        return () => {
+            unlistenList();
+            unlistenStatus();
+        };
     },
 
     /**
-     * Iterates through all servers in the store and updates their status
+     * Verifies a server exists and adds it to the list
      */
-    async syncAll() {
-        const currentList = get(servers);
-        await Promise.all(currentList.map(s => this.checkStatus(s.id)));
+    async add(serverName: string) {
+        servers.set(await invoke<ServerCard[]>('add_server', { id: serverName }));
     },
 
     /**
-     * Verifies a server exists and adds it to the list
+     * Servers the user is entitled to that match the query, with their metadata
      */
-    async add(serverName: string) {
-        const currentList = get(servers);
-
-        if (currentList.some(s => s.id === serverName)) {
-            throw new Error("ERR_DUPLICATE");
-        }
-
-        const data = await api.get<{ exists: boolean }>(`/users/servers/${serverName}/exists`);
-        if (!data.exists) throw new Error("ERR_NOT_FOUND");
+    async search(query: string, limit = 20): Promise<ServerInfo[]> {
+        return await invoke<ServerInfo[]>('search_servers', { query, limit });
+    },
 
-        const newServer: ServerCard = { id: serverName, status: 'idle' };
+    /**
+     * Requests access to a server; the status arrives as an event
+     */
+    async requestAccess(serverName: string) {
+        await invoke('request_server_access', { id: serverName });
+    },
 
-        servers.update(list => {
-            const updated = [...list, newServer];
-            saveToDisk(updated);
-            return updated;
-        });
+    /**
+     * Replaces the groups (tags) of a server
+     */
+    async setGroups(serverName: string, groups: string[]) {
+        servers.set(await invoke<ServerCard[]>('set_server_groups', { id: serverName, groups }));
     },
 
     /**
-     * Requests access to a server
+     * Requests access to every server in a group; one result per server
      */
-    async requestAccess(serverName: string) {
-        await api.post('/users/access', { server_id: serverName });
+    async requestGroupAccess(group: string): Promise<AccessResult[]> {
+        return await invoke<AccessResult[]>('request_group_access', { group });
+    },
 
-        updateServerState(serverName, { status: 'access-granted' });
+    /**
+     * Pins a server: Rust renews its access shortly before it expires
+     */
+    async setKeepAlive(serverName: string, enabled: boolean) {
+        servers.set(await invoke<ServerCard[]>('set_keep_alive', { id: serverName, enabled }));
+    },
 
-        await this.checkStatus(serverName);
+    /**
+     * Recent keep-alive renewals, newest first
+     */
+    async keepAliveLog(limit = 20): Promise<RenewalRecord[]> {
+        return await invoke<RenewalRecord[]>('get_keep_alive_log', { limit });
     },
 
     /**
      * Removes a server from the list
      */
     async remove(serverName: string) {
-        servers.update(list => {
-            const updated = list.filter(s => s.id !== serverName);
-            saveToDisk(updated);
-            return updated;
-        });
+        servers.set(await invoke<ServerCard[]>('remove_server', { id: serverName }));
     }
 };
-
-
-function updateServerState(id: string, updates: Partial<ServerCard>) {
-    servers.update(list => {
-        return list.map(s =>
-            s.id === id ? { ...s, ...updates } : s
-        );
-    });
-}
-
-async function saveToDisk(list: ServerCard[]) {
-    await db.set(KEY_SAVED_SERVERS, list);
-    await db.save();
-}
\ No newline at end of file
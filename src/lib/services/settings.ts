@@ -1,5 +1,6 @@
-import { isEnabled, enable, disable } from '@tauri-apps/plugin-autostart';
+import { isEnabled } from '@tauri-apps/plugin-autostart';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { db } from '$lib/stores/app-db';
 import { api } from '$lib/services/api';
 
@@ -7,9 +8,22 @@ import {
     serverUrl,
     isSettingsLoaded,
     autoStartEnabled,
+    autoStartLocked,
+    lockedKeys,
     DEFAULT_URL, extensionsEnabled
 } from '$lib/stores/settings';
 
+interface PolicySummary {
+    lockedKeys: string[];
+    autostartLocked: boolean;
+    allowedExtensions: string[] | null;
+}
+
+export interface ShortcutFailure {
+    shortcut: string;
+    error: string;
+}
+
 const KEY_SERVER_URL = 'server_url';
 const KEY_EXTENSIONS_ENABLED = 'extensions_enabled';
 
@@ -21,15 +35,17 @@ export const SettingsService = {
      */
     async load() {
         try {
-            const [urlVal, autoStartVal, extensionsVal] = await Promise.all([
-                db.get<string>(KEY_SERVER_URL),
+            const [settings, autoStartVal, policy] = await Promise.all([
+                db.all(),
                 isEnabled().catch(() => false),
-                db.get<boolean>(KEY_EXTENSIONS_ENABLED)
+                invoke<PolicySummary>('get_policy')
             ]);
 
-            serverUrl.set(urlVal || DEFAULT_URL);
+            serverUrl.set(settings.server_url || DEFAULT_URL);
             autoStartEnabled.set(!!autoStartVal);
-            extensionsEnabled.set(!!extensionsVal);
+            extensionsEnabled.set(settings.extensions_enabled);
+            lockedKeys.set(policy.lockedKeys);
+            autoStartLocked.set(policy.autostartLocked);
 
         } catch (err) {
             console.error("[SettingsService] Load failed:", err);
@@ -44,17 +60,9 @@ export const SettingsService = {
      */
     async toggleAutoStart(): Promise<boolean> {
         const current = await isEnabled();
-
-        if (current) {
-            await disable();
-            autoStartEnabled.set(false);
-            return false;
-        } else {
-            await enable();
-            await invoke('fix_autostart_path');
-            autoStartEnabled.set(true);
-            return true;
-        }
+        const enabled = await invoke<boolean>('set_autostart', { enabled: !current });
+        autoStartEnabled.set(enabled);
+        return enabled;
     },
 
     /**
@@ -70,7 +78,6 @@ export const SettingsService = {
             const newValue = !current;
 
             await db.set(KEY_EXTENSIONS_ENABLED, newValue);
-            await db.save();
 
             extensionsEnabled.set(newValue);
             return newValue;
@@ -80,6 +87,20 @@ export const SettingsService = {
         }
     },
 
+    /**
+     * Shortcuts that could not be registered the last time they were applied
+     */
+    async shortcutFailures(): Promise<ShortcutFailure[]> {
+        return await invoke<ShortcutFailure[]>('get_shortcut_failures');
+    },
+
+    /**
+     * Calls back whenever the shortcuts are re-applied. Returns the unlisten function.
+     */
+    async onShortcutFailures(callback: (failures: ShortcutFailure[]) => void): Promise<UnlistenFn> {
+        return await listen<ShortcutFailure[]>('shortcut-failures', (event) => callback(event.payload));
+    },
+
     /**
      * Validates, Checks Connection, and Saves the new URL
      * Updates: serverUrl, DB
@@ -94,7 +115,7 @@ export const SettingsService = {
         try {
             await api.checkConnection(candidateUrl);
         } catch (err: any) {
-            const msg = err.name === 'AbortError' ? "Connection timed out" : "Could not reach server health endpoint";
+            const msg = err.message === 'ERR_NETWORK' ? "Connection timed out" : "Could not reach server health endpoint";
             throw new Error(msg);
         }
 
@@ -105,7 +126,6 @@ export const SettingsService = {
             serverUrl.set(cleanUrl);
 
             await db.set(KEY_SERVER_URL, cleanUrl);
-            await db.save();
         } catch (err) {
             console.error("[SettingsService] Save failed:", err);
             throw new Error("Failed to save configuration to disk");
@@ -1,3 +1,65 @@
-import { LazyStore } from '@tauri-apps/plugin-store';
+import { invoke } from '@tauri-apps/api/core';
 
-export const db = new LazyStore('settings.json');
\ No newline at end of file
+/**
+ * Mirror of the Rust `Settings` struct. Keys match settings.json, which is why
+ * they are snake_case. The file is owned by Rust (core::settings): it validates,
+ * migrates and writes it atomically, so the UI only reads and patches it.
+ */
+export interface ServerShortcut {
+    shortcut: string;
+    server: string;
+}
+
+export type ScheduleTarget = { server: string } | { group: string };
+
+/** A cron expression (five fields, e.g. `30 8 * * Mon-Fri`) or a single RFC 3339 time. */
+export type ScheduleRule = { cron: string } | { once: string };
+
+export interface AccessSchedule {
+    id: string;
+    target: ScheduleTarget;
+    rule: ScheduleRule;
+    enabled: boolean;
+}
+
+export interface Settings {
+    schema_version: number;
+    server_url: string;
+    username: string | null;
+    oidc_issuer: string | null;
+    oidc_client_id: string | null;
+    active_profile: string;
+    jwks_url: string | null;
+    jwt_issuer: string | null;
+    jwt_audience: string | null;
+    refresh_interval_secs: number;
+    extensions_enabled: boolean;
+    saved_servers: { id: string; keep_alive?: boolean; groups?: string[] }[];
+    expiry_notifications: boolean;
+    expiry_warning_minutes: number[];
+    keep_alive_daily_limit: number;
+    keep_alive_hours: string | null;
+    keep_alive_weekdays_only: boolean;
+    dashboard_shortcut: string | null;
+    server_shortcuts: ServerShortcut[];
+    access_schedules: AccessSchedule[];
+    [key: string]: unknown;
+}
+
+export const db = {
+    async all(): Promise<Settings> {
+        return await invoke<Settings>('get_settings');
+    },
+
+    async get<T>(key: keyof Settings): Promise<T | null> {
+        const settings = await this.all();
+        return (settings[key] ?? null) as T | null;
+    },
+
+    /**
+     * Writes immediately. Rejects with a message if the value does not validate.
+     */
+    async set(key: keyof Settings, value: unknown): Promise<Settings> {
+        return await invoke<Settings>('update_settings', { patch: { [key]: value } });
+    },
+};
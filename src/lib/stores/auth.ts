@@ -6,7 +6,28 @@ export interface Session {
     username: string | null;
 }
 
+export interface DeviceLoginPrompt {
+    userCode: string;
+    verificationUri: string;
+    verificationUriComplete: string | null;
+    expiresIn: number;
+}
+
+export interface SessionInfo {
+    profile: string;
+    method: 'password' | 'sso';
+    subject: string | null;
+    username: string | null;
+    roles: string[];
+    groups: string[];
+    issuedAt: number | null;
+    expiresAt: number;
+    expiresIn: number;
+}
+
 export const isAuthenticated = writable<boolean>(false);
 export const authLoading = writable<boolean>(false);
 export const authError = writable<string>("");
-export const authSession = writable<Session | null>(null);
\ No newline at end of file
+export const authSession = writable<Session | null>(null);
+export const sessionInfo = writable<SessionInfo | null>(null);
+export const deviceLogin = writable<DeviceLoginPrompt | null>(null);
\ No newline at end of file
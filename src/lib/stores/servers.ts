@@ -4,6 +4,10 @@ export interface ServerCard {
     id: string;
     status: 'idle' | 'access-granted' | 'offline' | 'error';
     timeRemaining?: string | null;
+    expiration?: number | null;
+    ip?: string | null;
+    keepAlive?: boolean;
+    groups?: string[];
 }
 
-export const servers = writable<ServerCard[]>([]);
\ No newline at end of file
+export const servers = writable<ServerCard[]>([]);
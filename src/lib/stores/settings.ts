@@ -7,4 +7,8 @@ export const serverUrl = writable<string>(DEFAULT_URL);
 export const isSettingsLoaded = writable<boolean>(false);
 
 export const autoStartEnabled = writable<boolean>(false);
-export const extensionsEnabled = writable<boolean>(false);
\ No newline at end of file
+export const extensionsEnabled = writable<boolean>(false);
+
+// Keys pinned by the admin policy; read-only in the UI.
+export const lockedKeys = writable<string[]>([]);
+export const autoStartLocked = writable<boolean>(false);